@@ -84,7 +84,7 @@ impl Arch {
             })
         }) {
             return Some(arch);
-        };
+        }
 
         /*
             HACK: If nothing else matched, but the search string contains "universal",
@@ -137,10 +137,7 @@ mod tests {
 
     #[test]
     fn substrings_and_words_are_lowercase() {
-        for (arch, keywords) in ARCH_SUBSTRINGS
-            .into_iter()
-            .chain(ARCH_FULL_WORDS.into_iter())
-        {
+        for (arch, keywords) in ARCH_SUBSTRINGS.into_iter().chain(ARCH_FULL_WORDS) {
             for keyword in keywords {
                 assert_eq!(
                     keyword.to_string(),
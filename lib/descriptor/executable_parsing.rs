@@ -3,6 +3,40 @@ use tokio::time::Instant;
 
 use super::{Arch, OS};
 
+const SHEBANG_MAGIC: &[u8] = b"#!";
+const ELF_MAGIC: &[u8] = b"\x7fELF";
+const PE_MAGIC: &[u8] = b"MZ";
+const MACH_MAGICS: [&[u8]; 6] = [
+    b"\xfe\xed\xfa\xce", // 32-bit, big endian
+    b"\xfe\xed\xfa\xcf", // 64-bit, big endian
+    b"\xce\xfa\xed\xfe", // 32-bit, little endian
+    b"\xcf\xfa\xed\xfe", // 64-bit, little endian
+    b"\xca\xfe\xba\xbe", // Universal (fat) binary
+    b"\xca\xfe\xba\xbf", // Universal (fat) binary, 64-bit offsets
+];
+
+/**
+    Checks if the given contents start with the magic bytes of a native
+    executable for the given operating system - ELF on Linux, Mach-O on
+    macOS, and PE on Windows - or with a `#!` shebang, for scripts.
+
+    This is only a lightweight sanity check, meant to catch mislabeled
+    files such as HTML error pages, and does not parse the contents.
+*/
+pub fn has_executable_magic(binary_contents: impl AsRef<[u8]>, os: OS) -> bool {
+    let binary_contents = binary_contents.as_ref();
+    if binary_contents.starts_with(SHEBANG_MAGIC) {
+        return true;
+    }
+    match os {
+        OS::Linux => binary_contents.starts_with(ELF_MAGIC),
+        OS::MacOS => MACH_MAGICS
+            .iter()
+            .any(|magic| binary_contents.starts_with(magic)),
+        OS::Windows => binary_contents.starts_with(PE_MAGIC),
+    }
+}
+
 /**
     Tries to parse the contents of and executable file and
     return the OS and architecture it was compiled for.
@@ -124,3 +158,41 @@ fn parse_pe(binary_contents: &[u8]) -> Option<(OS, Arch)> {
         Some((OS::Windows, arch))
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn executable_magic_linux() {
+        assert!(has_executable_magic(b"\x7fELF\x02\x01\x01", OS::Linux));
+        assert!(!has_executable_magic(b"MZ\x90\x00", OS::Linux));
+        assert!(!has_executable_magic(b"\xcf\xfa\xed\xfe", OS::Linux));
+    }
+
+    #[test]
+    fn executable_magic_macos() {
+        assert!(has_executable_magic(b"\xcf\xfa\xed\xfe\x07", OS::MacOS));
+        assert!(has_executable_magic(b"\xca\xfe\xba\xbe\x00", OS::MacOS));
+        assert!(!has_executable_magic(b"\x7fELF\x02\x01\x01", OS::MacOS));
+        assert!(!has_executable_magic(b"MZ\x90\x00", OS::MacOS));
+    }
+
+    #[test]
+    fn executable_magic_windows() {
+        assert!(has_executable_magic(b"MZ\x90\x00", OS::Windows));
+        assert!(!has_executable_magic(b"\x7fELF\x02\x01\x01", OS::Windows));
+        assert!(!has_executable_magic(b"\xcf\xfa\xed\xfe", OS::Windows));
+    }
+
+    #[test]
+    fn executable_magic_scripts_and_garbage() {
+        for os in [OS::Linux, OS::MacOS, OS::Windows] {
+            assert!(has_executable_magic(b"#!/bin/sh\necho hi", os));
+            assert!(!has_executable_magic(b"<!DOCTYPE html><html>", os));
+            assert!(!has_executable_magic(b"Not Found", os));
+            assert!(!has_executable_magic(b"", os));
+            assert!(!has_executable_magic(b"M", os));
+        }
+    }
+}
@@ -1,4 +1,4 @@
-use std::{cmp::Ordering, str::FromStr};
+use std::{cmp::Ordering, fmt, str::FromStr};
 
 use thiserror::Error;
 
@@ -9,6 +9,8 @@ mod toolchain;
 
 use self::executable_parsing::parse_executable;
 
+pub(crate) use self::executable_parsing::has_executable_magic;
+
 pub use self::arch::Arch;
 pub use self::os::OS;
 pub use self::toolchain::Toolchain;
@@ -158,6 +160,16 @@ impl Descriptor {
             return a.arch.cmp(&b.arch);
         }
         if a.toolchain != b.toolchain {
+            // NOTE: Binaries for glibc will not run on musl systems, so on those we
+            // prefer musl, then unmarked binaries which may be statically linked
+            if self.toolchain == Some(Toolchain::Musl) {
+                let rank = |t: Option<Toolchain>| match t {
+                    Some(Toolchain::Musl) => 0,
+                    None => 1,
+                    Some(_) => 2,
+                };
+                return rank(a.toolchain).cmp(&rank(b.toolchain));
+            }
             return a.toolchain.cmp(&b.toolchain);
         }
 
@@ -166,6 +178,19 @@ impl Descriptor {
     }
 }
 
+impl fmt::Display for Descriptor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.os.as_str())?;
+        if let Some(arch) = self.arch {
+            write!(f, "-{}", arch.as_str())?;
+        }
+        if let Some(toolchain) = self.toolchain {
+            write!(f, "-{}", toolchain.as_str())?;
+        }
+        Ok(())
+    }
+}
+
 impl FromStr for Descriptor {
     type Err = DescriptionParseError;
     fn from_str(s: &str) -> Result<Self, Self::Err> {
@@ -335,6 +360,14 @@ mod tests {
         }
     }
 
+    #[test]
+    fn display_description() {
+        let display = |s: &str| s.parse::<Descriptor>().unwrap().to_string();
+        assert_eq!(display("windows-x86_64-msvc"), "windows-x64-msvc");
+        assert_eq!(display("aarch64-apple-darwin"), "macos-arm64");
+        assert_eq!(display("linux"), "linux");
+    }
+
     #[test]
     fn parse_from_str_valid() {
         const VALID_STRINGS: &[&str] = &[
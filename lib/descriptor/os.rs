@@ -74,7 +74,7 @@ impl OS {
             })
         }) {
             return Some(os);
-        };
+        }
 
         None
     }
@@ -111,7 +111,7 @@ mod tests {
 
     #[test]
     fn substrings_and_words_are_lowercase() {
-        for (os, keywords) in OS_SUBSTRINGS.into_iter().chain(OS_FULL_WORDS.into_iter()) {
+        for (os, keywords) in OS_SUBSTRINGS.into_iter().chain(OS_FULL_WORDS) {
             for keyword in keywords {
                 assert_eq!(
                     keyword.to_string(),
@@ -19,10 +19,21 @@ pub enum Toolchain {
 impl Toolchain {
     /**
         Get the toolchain of the current host system.
+
+        This is the toolchain that the current binary was built with,
+        meaning that a musl-linked Rokit will prefer musl-linked tools.
     */
     #[must_use]
     pub fn current_system() -> Option<Self> {
-        None // TODO: Implement detection of the host toolchain
+        if cfg!(target_env = "musl") {
+            Some(Self::Musl)
+        } else if cfg!(target_env = "gnu") {
+            Some(Self::Gnu)
+        } else if cfg!(target_env = "msvc") {
+            Some(Self::Msvc)
+        } else {
+            None
+        }
     }
 
     /**
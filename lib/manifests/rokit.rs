@@ -220,7 +220,7 @@ impl FromStr for RokitManifest {
                     \nThe tool will be ignored and may not be available.\
                     \nError: {e}",
                 );
-            };
+            }
             let Some(spec_str) = value.as_str() else {
                 warn!(
                     "A tool spec with alias '{}' could not be parsed!\
@@ -239,7 +239,7 @@ impl FromStr for RokitManifest {
                     \nError: {e}",
                     keys.into_iter().last().unwrap(),
                 );
-            };
+            }
         }
 
         Ok(Self { document })
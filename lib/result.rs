@@ -1,14 +1,20 @@
-use std::io::Error as IoError;
+use std::io::{Error as IoError, ErrorKind as IoErrorKind};
 use std::path::PathBuf;
 
 use postcard::Error as PostcardError;
+use semver::{Error as SemverError, Version};
 use serde_json::Error as JsonError;
+use sevenz_rust::Error as SevenZError;
 use thiserror::Error;
 use tokio::task::JoinError;
 use toml_edit::TomlError;
 use zip::result::ZipError;
 
-use crate::sources::{github::GithubError, ExtractError};
+use crate::{
+    descriptor::Descriptor,
+    sources::{bitbucket::BitbucketError, github::GithubError, gitlab::GitlabError, ExtractError},
+    tool::{ToolId, ToolVersion},
+};
 
 #[derive(Debug, Error)]
 pub enum RokitError {
@@ -18,6 +24,41 @@ pub enum RokitError {
     FileNotFound(PathBuf),
     #[error("unexpected invalid UTF-8")]
     InvalidUtf8,
+    #[error("rate limit exceeded - resets at unix time {reset_at}")]
+    RateLimited { reset_at: u64 },
+    #[error("no release was found with tag '{tag}'")]
+    ReleaseNotFound { tag: String },
+    #[error("request to '{url}' failed with HTTP status {status}")]
+    Http { status: u16, url: String },
+    #[error(
+        "tool '{id}' version {version} is not cached, and can not be downloaded while offline"
+    )]
+    NotCached { id: Box<ToolId>, version: Version },
+    #[error("no published version of tool '{id}' matches '{version}'")]
+    NoMatchingVersion {
+        id: Box<ToolId>,
+        version: Box<ToolVersion>,
+    },
+    #[error("conflicting version requirements for tool '{id}' - '{first}' and '{second}'")]
+    ConflictingRequirements {
+        id: Box<ToolId>,
+        first: Box<ToolVersion>,
+        second: Box<ToolVersion>,
+    },
+    #[error("no artifact was found that is compatible with '{0}'")]
+    NoCompatibleArtifact(Descriptor),
+    #[error("checksum mismatch - expected '{expected}', got '{actual}'")]
+    ChecksumMismatch { expected: String, actual: String },
+    #[error(
+        "file '{file_name}' in '{archive_name}' is not an executable \
+        for the current system - the release asset may be mislabeled"
+    )]
+    NotAnExecutable {
+        file_name: String,
+        archive_name: String,
+    },
+    #[error("archive contains unsafe entry path: {0}")]
+    UnsafeArchivePath(String),
     #[error("failed to extract artifact: {0}")]
     Extract(Box<ExtractError>),
     #[error("task join error: {0}")]
@@ -30,10 +71,88 @@ pub enum RokitError {
     Json(Box<JsonError>),
     #[error("Postcard error: {0}")]
     Postcard(Box<PostcardError>),
+    #[error("version parse error: {0}")]
+    SemverParse(Box<SemverError>),
     #[error("Zip file error: {0}")]
     Zip(Box<ZipError>),
+    #[error("7z file error: {0}")]
+    SevenZ(Box<SevenZError>),
     #[error("GitHub error: {0}")]
     GitHub(Box<GithubError>),
+    #[error("GitLab error: {0}")]
+    GitLab(Box<GitlabError>),
+    #[error("Bitbucket error: {0}")]
+    Bitbucket(Box<BitbucketError>),
+}
+
+impl RokitError {
+    /**
+        Checks if this error was caused by a resource not being found,
+        such as a `404 Not Found` HTTP response, or a missing release.
+    */
+    #[must_use]
+    pub fn is_not_found(&self) -> bool {
+        match self {
+            Self::Http { status, .. } => *status == 404,
+            Self::ReleaseNotFound { .. } => true,
+            Self::GitHub(err) => matches!(
+                **err,
+                GithubError::ToolNotFound(_)
+                    | GithubError::LatestReleaseNotFound(_)
+                    | GithubError::ReleaseNotFound(_)
+            ),
+            Self::GitLab(err) => matches!(
+                **err,
+                GitlabError::ToolNotFound(_)
+                    | GitlabError::LatestReleaseNotFound(_)
+                    | GitlabError::ReleaseNotFound(_)
+            ),
+            Self::Bitbucket(err) => matches!(
+                **err,
+                BitbucketError::ToolNotFound(_)
+                    | BitbucketError::LatestReleaseNotFound(_)
+                    | BitbucketError::ReleaseNotFound(_)
+            ),
+            _ => false,
+        }
+    }
+
+    /**
+        Checks if this error was caused by an archive that could not be read
+        in its detected format, such as a truncated download or a zip file
+        with a bad checksum, where downloading the archive again may help.
+
+        Unknown formats, missing binaries, and other errors that
+        would not change by downloading again are never included.
+    */
+    #[must_use]
+    pub fn is_corrupt_archive(&self) -> bool {
+        match self {
+            Self::Io(err) => matches!(
+                err.kind(),
+                IoErrorKind::InvalidData | IoErrorKind::UnexpectedEof
+            ),
+            Self::Zip(err) => matches!(**err, ZipError::InvalidArchive(_) | ZipError::Io(_)),
+            Self::SevenZ(err) => matches!(
+                **err,
+                SevenZError::Io(..)
+                    | SevenZError::ChecksumVerificationFailed
+                    | SevenZError::NextHeaderCrcMismatch
+                    | SevenZError::BadTerminatedStreamsInfo(_)
+                    | SevenZError::BadTerminatedUnpackInfo
+                    | SevenZError::BadTerminatedPackInfo(_)
+                    | SevenZError::BadTerminatedSubStreamsInfo
+                    | SevenZError::BadTerminatedheader(_)
+            ),
+            Self::Extract(err) => match &**err {
+                ExtractError::Generic { source, .. } => source
+                    .downcast_ref::<RokitError>()
+                    .is_some_and(RokitError::is_corrupt_archive),
+                _ => false,
+            },
+            _ => false,
+        }
+    }
 }
 
 pub type RokitResult<T> = Result<T, RokitError>;
@@ -76,14 +195,71 @@ impl From<PostcardError> for RokitError {
     }
 }
 
+impl From<SemverError> for RokitError {
+    fn from(err: SemverError) -> Self {
+        RokitError::SemverParse(err.into())
+    }
+}
+
 impl From<ZipError> for RokitError {
     fn from(err: ZipError) -> Self {
         RokitError::Zip(err.into())
     }
 }
 
+impl From<SevenZError> for RokitError {
+    fn from(err: SevenZError) -> Self {
+        RokitError::SevenZ(err.into())
+    }
+}
+
 impl From<GithubError> for RokitError {
     fn from(err: GithubError) -> Self {
-        RokitError::GitHub(err.into())
+        match err {
+            GithubError::RateLimited { reset_at } => RokitError::RateLimited { reset_at },
+            GithubError::ReleaseTagNotFound { tag } => RokitError::ReleaseNotFound { tag },
+            GithubError::Http { status, url } => RokitError::Http { status, url },
+            err => RokitError::GitHub(err.into()),
+        }
+    }
+}
+
+impl From<GitlabError> for RokitError {
+    fn from(err: GitlabError) -> Self {
+        match err {
+            GitlabError::RateLimited { reset_at } => RokitError::RateLimited { reset_at },
+            GitlabError::ReleaseTagNotFound { tag } => RokitError::ReleaseNotFound { tag },
+            GitlabError::Http { status, url } => RokitError::Http { status, url },
+            err => RokitError::GitLab(err.into()),
+        }
+    }
+}
+
+impl From<BitbucketError> for RokitError {
+    fn from(err: BitbucketError) -> Self {
+        match err {
+            BitbucketError::RateLimited { reset_at } => RokitError::RateLimited { reset_at },
+            BitbucketError::ReleaseTagNotFound { tag } => RokitError::ReleaseNotFound { tag },
+            BitbucketError::Http { status, url } => RokitError::Http { status, url },
+            err => RokitError::Bitbucket(err.into()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse_version(s: &str) -> RokitResult<Version> {
+        Ok(s.parse::<Version>()?)
+    }
+
+    #[test]
+    fn semver_parse_error() {
+        assert!(parse_version("1.2.3").is_ok());
+        assert!(matches!(
+            parse_version("not-a-version"),
+            Err(RokitError::SemverParse(_))
+        ));
     }
 }
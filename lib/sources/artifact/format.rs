@@ -2,6 +2,14 @@ use std::{fmt, str::FromStr};
 
 use super::util::split_filename_and_extensions;
 
+const ZIP_MAGIC: &[u8] = b"PK\x03\x04";
+const ZIP_MAGIC_EMPTY: &[u8] = b"PK\x05\x06";
+const GZIP_MAGIC: &[u8] = b"\x1f\x8b";
+const XZ_MAGIC: &[u8] = b"\xfd7zXZ\x00";
+const TAR_MAGIC: &[u8] = b"ustar";
+const TAR_MAGIC_OFFSET: usize = 257;
+const SEVEN_Z_MAGIC: &[u8] = b"7z\xbc\xaf\x27\x1c";
+
 /**
     An artifact format supported by Rokit.
 */
@@ -10,7 +18,12 @@ pub enum ArtifactFormat {
     Zip,
     Tar,
     TarGz,
+    TarXz,
     Gz,
+    SevenZ,
+    // NOTE: Plain binaries are never detected from names or
+    // contents, they are only used as a fallback during extraction
+    Binary,
 }
 
 impl ArtifactFormat {
@@ -20,7 +33,10 @@ impl ArtifactFormat {
             Self::Zip => "zip",
             Self::Tar => "tar",
             Self::TarGz => "tar.gz",
+            Self::TarXz => "tar.xz",
             Self::Gz => "gz",
+            Self::SevenZ => "7z",
+            Self::Binary => "binary",
         }
     }
 
@@ -30,11 +46,18 @@ impl ArtifactFormat {
             [.., ext] if ext.eq_ignore_ascii_case("zip") => Some(Self::Zip),
             [.., ext] if ext.eq_ignore_ascii_case("tar") => Some(Self::Tar),
             [.., ext] if ext.eq_ignore_ascii_case("tgz") => Some(Self::TarGz),
+            [.., ext] if ext.eq_ignore_ascii_case("txz") => Some(Self::TarXz),
+            [.., ext] if ext.eq_ignore_ascii_case("7z") => Some(Self::SevenZ),
             [.., ext1, ext2]
                 if ext1.eq_ignore_ascii_case("tar") && ext2.eq_ignore_ascii_case("gz") =>
             {
                 Some(Self::TarGz)
             }
+            [.., ext1, ext2]
+                if ext1.eq_ignore_ascii_case("tar") && ext2.eq_ignore_ascii_case("xz") =>
+            {
+                Some(Self::TarXz)
+            }
             [.., ext] if ext.eq_ignore_ascii_case("gz") => Some(Self::Gz),
             _ => None,
         }
@@ -46,6 +69,36 @@ impl ArtifactFormat {
         let (_, extensions) = split_filename_and_extensions(path_or_url);
         Self::from_extensions(extensions)
     }
+
+    /**
+        Detects the format of an artifact from the magic bytes at the start of its contents.
+
+        Note that a gzip stream may contain either a tar archive or a single
+        file, and this method will always assume it contains a tar archive -
+        the decompressed contents must be inspected to tell the two apart.
+
+        Returns `None` if the contents are not in any recognized format.
+    */
+    #[must_use]
+    pub fn from_contents(contents: impl AsRef<[u8]>) -> Option<Self> {
+        let contents = contents.as_ref();
+        if contents.starts_with(ZIP_MAGIC) || contents.starts_with(ZIP_MAGIC_EMPTY) {
+            Some(Self::Zip)
+        } else if contents.starts_with(GZIP_MAGIC) {
+            Some(Self::TarGz)
+        } else if contents.starts_with(XZ_MAGIC) {
+            Some(Self::TarXz)
+        } else if contents.starts_with(SEVEN_Z_MAGIC) {
+            Some(Self::SevenZ)
+        } else if contents
+            .get(TAR_MAGIC_OFFSET..)
+            .is_some_and(|rest| rest.starts_with(TAR_MAGIC))
+        {
+            Some(Self::Tar)
+        } else {
+            None
+        }
+    }
 }
 
 impl FromStr for ArtifactFormat {
@@ -56,6 +109,8 @@ impl FromStr for ArtifactFormat {
             "zip" => Ok(Self::Zip),
             "tar" => Ok(Self::Tar),
             "tar.gz" | "tgz" => Ok(Self::TarGz),
+            "tar.xz" | "txz" => Ok(Self::TarXz),
+            "7z" => Ok(Self::SevenZ),
             _ => Err(format!("unknown artifact format '{l}'")),
         }
     }
@@ -82,7 +137,10 @@ mod tests {
         assert_eq!(format_from_str("file.tar"), Some(ArtifactFormat::Tar));
         assert_eq!(format_from_str("file.tar.gz"), Some(ArtifactFormat::TarGz));
         assert_eq!(format_from_str("file.tgz"), Some(ArtifactFormat::TarGz));
+        assert_eq!(format_from_str("file.tar.xz"), Some(ArtifactFormat::TarXz));
+        assert_eq!(format_from_str("file.txz"), Some(ArtifactFormat::TarXz));
         assert_eq!(format_from_str("file.gz"), Some(ArtifactFormat::Gz));
+        assert_eq!(format_from_str("file.7z"), Some(ArtifactFormat::SevenZ));
         assert_eq!(
             format_from_str("file.with.many.extensions.tar.gz.zip"),
             Some(ArtifactFormat::Zip)
@@ -126,6 +184,45 @@ mod tests {
             format_from_str("lefthook_1.7.14_Windows_x86_64.gz"),
             Some(ArtifactFormat::Gz)
         );
+        assert_eq!(
+            format_from_str("zig-linux-x86_64-0.13.0.tar.xz"),
+            Some(ArtifactFormat::TarXz)
+        );
+    }
+
+    #[test]
+    fn format_from_contents_valid() {
+        let mut tar = vec![0; 512];
+        tar[TAR_MAGIC_OFFSET..TAR_MAGIC_OFFSET + TAR_MAGIC.len()].copy_from_slice(TAR_MAGIC);
+        assert_eq!(
+            ArtifactFormat::from_contents(b"PK\x03\x04rest-of-zip"),
+            Some(ArtifactFormat::Zip)
+        );
+        assert_eq!(
+            ArtifactFormat::from_contents(b"\x1f\x8b\x08rest-of-gzip"),
+            Some(ArtifactFormat::TarGz)
+        );
+        assert_eq!(
+            ArtifactFormat::from_contents(b"\xfd7zXZ\x00rest-of-xz"),
+            Some(ArtifactFormat::TarXz)
+        );
+        assert_eq!(
+            ArtifactFormat::from_contents(b"7z\xbc\xaf\x27\x1crest-of-7z"),
+            Some(ArtifactFormat::SevenZ)
+        );
+        assert_eq!(
+            ArtifactFormat::from_contents(tar),
+            Some(ArtifactFormat::Tar)
+        );
+    }
+
+    #[test]
+    fn format_from_contents_invalid() {
+        assert_eq!(ArtifactFormat::from_contents(b""), None);
+        assert_eq!(ArtifactFormat::from_contents(b"PK"), None);
+        assert_eq!(ArtifactFormat::from_contents(b"<!DOCTYPE html>"), None);
+        assert_eq!(ArtifactFormat::from_contents(b"\x7fELF\x02\x01\x01"), None);
+        assert_eq!(ArtifactFormat::from_contents(vec![0; 1024]), None);
     }
 
     #[test]
@@ -142,6 +239,8 @@ mod tests {
         assert_eq!(format_from_str("file.tgz"), Some(ArtifactFormat::TarGz));
         assert_eq!(format_from_str("file.TGZ"), Some(ArtifactFormat::TarGz));
         assert_eq!(format_from_str("file.Tgz"), Some(ArtifactFormat::TarGz));
+        assert_eq!(format_from_str("file.tar.xz"), Some(ArtifactFormat::TarXz));
+        assert_eq!(format_from_str("file.TAR.XZ"), Some(ArtifactFormat::TarXz));
         assert_eq!(format_from_str("file.gz"), Some(ArtifactFormat::Gz));
         assert_eq!(format_from_str("file.GZ"), Some(ArtifactFormat::Gz));
         assert_eq!(format_from_str("file.Gz"), Some(ArtifactFormat::Gz));
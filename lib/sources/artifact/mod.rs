@@ -1,17 +1,29 @@
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+};
+
+use semver::{BuildMetadata, Version};
 use tracing::instrument;
 use url::Url;
 
 use crate::{
-    descriptor::{Descriptor, OS},
-    result::RokitResult,
+    descriptor::{has_executable_magic, Descriptor, OS},
+    result::{RokitError, RokitResult},
     tool::ToolSpec,
 };
 
 use super::{
-    decompression::decompress_gzip,
-    extraction::{extract_tar_file, extract_zip_file},
+    bitbucket::models::BitbucketDownload,
+    checksum::verify_sha256,
+    decompression::{decompress_gzip, decompress_xz},
+    extraction::{
+        extract_7z_files, extract_7z_matching, extract_tar_files, extract_tar_matching,
+        extract_zip_files, extract_zip_matching,
+    },
     github::models::GithubAsset,
-    ExtractError,
+    gitlab::models::GitlabAssetLink,
+    ExtractError, ExtractedBinary, ExtractedFile,
 };
 
 mod format;
@@ -20,10 +32,13 @@ mod sorting;
 mod util;
 
 use self::sorting::sort_preferred_artifact;
-use self::util::split_filename_and_extensions;
+pub(crate) use self::util::split_filename_and_extensions;
 
 pub use self::format::ArtifactFormat;
 pub use self::provider::ArtifactProvider;
+pub use self::util::normalize_asset_name;
+
+const WINDOWS_SCRIPT_EXTENSIONS: [&str; 3] = ["bat", "cmd", "ps1"];
 
 /**
     A release found by Rokit, containing a list
@@ -35,6 +50,29 @@ pub struct Release {
     pub artifacts: Vec<Artifact>,
 }
 
+impl Release {
+    /**
+        Gets a version from a release tag, as leniently as possible.
+
+        Tags that are not valid semver versions, such as `nightly`, are
+        stored as build metadata of a `0.0.0` version - `0.0.0+nightly`.
+    */
+    pub(crate) fn version_from_tag(tag: &str) -> Version {
+        let tag = tag.trim();
+        if let Ok(version) = tag.trim_start_matches('v').parse::<Version>() {
+            return version;
+        }
+
+        let sanitized = tag
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() { c } else { '-' })
+            .collect::<String>();
+        let mut version = Version::new(0, 0, 0);
+        version.build = BuildMetadata::new(&sanitized).unwrap_or(BuildMetadata::EMPTY);
+        version
+    }
+}
+
 /**
     An artifact found by Rokit, to be downloaded and installed.
 */
@@ -45,6 +83,7 @@ pub struct Artifact {
     pub id: Option<String>,
     pub url: Option<Url>,
     pub name: Option<String>,
+    pub file_name: Option<String>,
     pub tool_spec: ToolSpec,
 }
 
@@ -58,6 +97,49 @@ impl Artifact {
             id: Some(asset.id.to_string()),
             url: Some(asset.url.clone()),
             name: Some(name.to_string()),
+            file_name: Some(asset.name.clone()),
+            tool_spec: spec.clone(),
+        }
+    }
+
+    pub(crate) fn from_gitlab_release_link(link: &GitlabAssetLink, spec: &ToolSpec) -> Self {
+        let (name, extensions) = split_filename_and_extensions(&link.name);
+        let format = ArtifactFormat::from_extensions(extensions);
+        Self {
+            provider: ArtifactProvider::GitLab,
+            format,
+            id: Some(link.id.to_string()),
+            url: Some(link.direct_asset_url.as_ref().unwrap_or(&link.url).clone()),
+            name: Some(name.to_string()),
+            file_name: Some(link.name.clone()),
+            tool_spec: spec.clone(),
+        }
+    }
+
+    pub(crate) fn from_bitbucket_download(download: &BitbucketDownload, spec: &ToolSpec) -> Self {
+        let (name, extensions) = split_filename_and_extensions(&download.name);
+        let format = ArtifactFormat::from_extensions(extensions);
+        Self {
+            provider: ArtifactProvider::Bitbucket,
+            format,
+            id: Some(download.name.clone()),
+            url: Some(download.links.self_link.href.clone()),
+            name: Some(name.to_string()),
+            file_name: Some(download.name.clone()),
+            tool_spec: spec.clone(),
+        }
+    }
+
+    pub(crate) fn from_cached_file_name(file_name: &str, spec: &ToolSpec) -> Self {
+        let (name, extensions) = split_filename_and_extensions(file_name);
+        let format = ArtifactFormat::from_extensions(extensions);
+        Self {
+            provider: spec.provider(),
+            format,
+            id: None,
+            url: None,
+            name: Some(name.to_string()),
+            file_name: Some(file_name.to_string()),
             tool_spec: spec.clone(),
         }
     }
@@ -66,62 +148,254 @@ impl Artifact {
         Extract the contents of the artifact.
 
         The given contents must be the raw bytes of the artifact,
-        in the expected format, as downloaded from the download URL.
+        as downloaded from the download URL.
+
+        The format of the artifact is detected from the contents themselves
+        when possible, since release assets are sometimes misnamed, and the
+        format found from the name of the artifact is used as a fallback.
+
+        If neither the contents nor the name are in a recognized archive
+        format, the contents are assumed to be the plain binary itself.
+
+        # Errors
+
+        - If the artifact could not be extracted, or the binary was missing.
+        - If the binary was found, but is not compatible with the current OS.
+    */
+    pub async fn extract_contents(&self, contents: Vec<u8>) -> RokitResult<ExtractedBinary> {
+        self.extract_contents_with_checksum(contents, None).await
+    }
+
+    /**
+        Extract the contents of the artifact, after verifying
+        that they match the given expected SHA256 hex digest.
+
+        Verification is skipped if no digest is given, in which
+        case this is equivalent to [`Artifact::extract_contents`].
 
-        This generally means that, as long as the same artifact provider
-        is used to both create and download the artifact, the format
-        should be known and the contents should be in the correct format.
+        # Errors
+
+        - If the contents do not match the expected digest.
+        - If the contents could not be extracted, see [`Artifact::extract_contents`].
     */
     #[instrument(skip(self, contents), level = "debug")]
-    pub async fn extract_contents(&self, contents: Vec<u8>) -> RokitResult<Vec<u8>> {
-        let format = self.format.ok_or(ExtractError::UnknownFormat)?;
+    pub async fn extract_contents_with_checksum(
+        &self,
+        contents: Vec<u8>,
+        expected_sha256: Option<String>,
+    ) -> RokitResult<ExtractedBinary> {
+        verify_sha256(&contents, expected_sha256)?;
+        self.extract_contents_with_progress(contents, |_, _| {})
+            .await
+    }
 
+    /**
+        Extract the contents of the artifact, calling the given callback
+        with the number of archive entries processed so far, and the total
+        number of entries in the archive, if known.
+
+        Plain binaries and bare gzip files are reported as a single entry.
+
+        # Errors
+
+        - If the contents could not be extracted, see [`Artifact::extract_contents`].
+    */
+    #[instrument(skip(self, contents, progress), level = "debug")]
+    pub async fn extract_contents_with_progress(
+        &self,
+        contents: Vec<u8>,
+        progress: impl FnMut(u64, Option<u64>) + Send + 'static,
+    ) -> RokitResult<ExtractedBinary> {
         let file_name = self.tool_spec.name().to_string();
-        let file_res = match format {
-            ArtifactFormat::Zip => extract_zip_file(&contents, &file_name).await,
-            ArtifactFormat::Tar => extract_tar_file(&contents, &file_name).await,
-            ArtifactFormat::TarGz => {
-                let tar = decompress_gzip(&contents).await?;
-                extract_tar_file(&tar, &file_name).await
+        let (format, mut files) = self
+            .extract_files_inner(contents, vec![file_name.clone()], progress)
+            .await?;
+
+        let binary = files
+            .remove(&file_name)
+            .ok_or_else(|| ExtractError::FileMissing {
+                format,
+                file_name,
+                archive_name: self.name.clone().unwrap_or_default(),
+            })?;
+
+        Ok(binary)
+    }
+
+    /**
+        Extract several binaries from the contents of the artifact,
+        for releases that bundle more than a single executable.
+
+        Returns a map of the given binary names to their extracted binaries.
+
+        Note that plain binaries and bare gzip files contain only a single
+        file, and can only be extracted when requesting a single binary.
+
+        # Errors
+
+        - If any of the binaries are missing, listing all missing binaries.
+        - If the contents could not be extracted, see [`Artifact::extract_contents`].
+    */
+    #[instrument(skip(self, contents), level = "debug")]
+    pub async fn extract_binaries(
+        &self,
+        contents: Vec<u8>,
+        binary_names: &[&str],
+    ) -> RokitResult<BTreeMap<String, ExtractedBinary>> {
+        let file_names = binary_names
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>();
+        let (format, files) = self
+            .extract_files_inner(contents, file_names.clone(), |_, _| {})
+            .await?;
+
+        let missing = file_names
+            .into_iter()
+            .filter(|file_name| !files.contains_key(file_name))
+            .collect::<Vec<_>>();
+        if !missing.is_empty() {
+            return Err(ExtractError::FilesMissing {
+                format,
+                file_names: missing,
+                archive_name: self.name.clone().unwrap_or_default(),
             }
-            ArtifactFormat::Gz => decompress_gzip(&contents).await.map(Some),
-        };
+            .into());
+        }
 
-        // Make sure we got back the file we need ...
+        Ok(files)
+    }
 
-        let file_opt = file_res.map_err(|err| ExtractError::Generic {
-            source: err.into(),
-            body: {
-                if contents.len() > 128 + 6 {
-                    let bytes = contents.iter().copied().take(128).collect::<Vec<_>>();
-                    format!("{} <...>", String::from_utf8_lossy(bytes.as_slice()).trim())
+    /**
+        Extract companion files, such as shell completions, licenses, or
+        shared libraries, that match any of the given glob-style patterns.
+
+        Patterns are matched against the full path of each file in the
+        archive, such as `completions/tool.*` or `*.so`, and matching files
+        are returned along with their sanitized paths, relative to the archive root.
+
+        Plain binaries and bare gzip files never contain any companion files.
+
+        # Errors
+
+        - If the archive contains any unsafe paths.
+        - If the contents could not be extracted.
+    */
+    #[instrument(skip(self, contents), level = "debug")]
+    pub async fn extract_included_files(
+        &self,
+        contents: Vec<u8>,
+        include: &[&str],
+    ) -> RokitResult<Vec<(PathBuf, ExtractedFile)>> {
+        let patterns = include.iter().map(ToString::to_string).collect::<Vec<_>>();
+        match self.detect_format(&contents) {
+            ArtifactFormat::Zip => extract_zip_matching(&contents, patterns).await,
+            ArtifactFormat::Tar => extract_tar_matching(&contents, patterns).await,
+            ArtifactFormat::TarGz | ArtifactFormat::Gz => {
+                let decompressed = decompress_gzip(&contents).await?;
+                if ArtifactFormat::from_contents(&decompressed) == Some(ArtifactFormat::Tar) {
+                    extract_tar_matching(&decompressed, patterns).await
                 } else {
-                    String::from_utf8_lossy(&contents).to_string()
+                    Ok(Vec::new())
                 }
-            },
-        })?;
+            }
+            ArtifactFormat::TarXz => {
+                let tar = decompress_xz(&contents).await?;
+                extract_tar_matching(&tar, patterns).await
+            }
+            ArtifactFormat::SevenZ => extract_7z_matching(&contents, patterns).await,
+            ArtifactFormat::Binary => Ok(Vec::new()),
+        }
+    }
 
-        let file_bytes = file_opt.ok_or_else(|| ExtractError::FileMissing {
-            format,
-            file_name: self.tool_spec.name().to_string(),
-            archive_name: self.name.clone().unwrap_or_default(),
+    fn detect_format(&self, contents: &[u8]) -> ArtifactFormat {
+        match (ArtifactFormat::from_contents(contents), self.format) {
+            (Some(detected), _) => detected,
+            (None, Some(named)) => named,
+            (None, None) => ArtifactFormat::Binary,
+        }
+    }
+
+    async fn extract_files_inner(
+        &self,
+        contents: Vec<u8>,
+        file_names: Vec<String>,
+        mut progress: impl FnMut(u64, Option<u64>) + Send + 'static,
+    ) -> RokitResult<(ArtifactFormat, BTreeMap<String, ExtractedBinary>)> {
+        let format = self.detect_format(&contents);
+
+        let files_res = match format {
+            ArtifactFormat::Zip => extract_zip_files(&contents, file_names, progress).await,
+            ArtifactFormat::Tar => extract_tar_files(&contents, file_names, progress).await,
+            ArtifactFormat::TarGz | ArtifactFormat::Gz => {
+                // Gzip streams may contain either a tar archive or a single file,
+                // regardless of their name, so look for a tar header to tell them apart
+                let decompressed = decompress_gzip(&contents).await?;
+                if ArtifactFormat::from_contents(&decompressed) == Some(ArtifactFormat::Tar) {
+                    extract_tar_files(&decompressed, file_names, progress).await
+                } else {
+                    progress(1, Some(1));
+                    Ok(single_file(file_names, decompressed))
+                }
+            }
+            ArtifactFormat::TarXz => {
+                let tar = decompress_xz(&contents).await?;
+                extract_tar_files(&tar, file_names, progress).await
+            }
+            ArtifactFormat::SevenZ => extract_7z_files(&contents, file_names, progress).await,
+            ArtifactFormat::Binary => {
+                progress(1, Some(1));
+                Ok(single_file(file_names, contents.clone()))
+            }
+        };
+
+        // Make sure we got back the files we need ...
+
+        let files = files_res.map_err(|err| match err {
+            // Unsafe archives are not malformed, and the body is not useful
+            RokitError::UnsafeArchivePath(_) => err,
+            err => ExtractError::Generic {
+                source: err.into(),
+                body: {
+                    if contents.len() > 128 + 6 {
+                        let bytes = contents.iter().copied().take(128).collect::<Vec<_>>();
+                        format!("{} <...>", String::from_utf8_lossy(bytes.as_slice()).trim())
+                    } else {
+                        String::from_utf8_lossy(&contents).to_string()
+                    }
+                },
+            }
+            .into(),
         })?;
 
-        // ... and parse the OS from the executable binary, or error,
-        // to ensure that the user will actually be able to run it
+        // ... and parse the OS from the executable binaries, or error,
+        // to ensure that the user will actually be able to run them
 
         let os_current = OS::current_system();
-        let os_file = OS::detect_from_executable(&file_bytes);
-        if os_file.is_some_and(|os| os != os_current) {
-            Err(ExtractError::OSMismatch {
-                current_os: os_current,
-                file_os: os_file.unwrap(),
-                file_name: self.tool_spec.name().to_string(),
-                archive_name: self.name.clone().unwrap_or_default(),
-            })?;
+        let is_plain_script = format == ArtifactFormat::Binary
+            && self.file_name.as_deref().is_some_and(is_windows_script);
+        for (file_name, binary) in &files {
+            let os_file = OS::detect_from_executable(&binary.contents);
+            if let Some(file_os) = os_file.filter(|os| *os != os_current) {
+                Err(ExtractError::OSMismatch {
+                    current_os: os_current,
+                    file_os,
+                    file_name: file_name.clone(),
+                    archive_name: self.name.clone().unwrap_or_default(),
+                })?;
+            }
+            // NOTE: Windows scripts have no magic bytes or shebang to check for
+            let is_script =
+                os_current == OS::Windows && (is_plain_script || is_windows_script(file_name));
+            if !is_script && !has_executable_magic(&binary.contents, os_current) {
+                return Err(RokitError::NotAnExecutable {
+                    file_name: file_name.clone(),
+                    archive_name: self.name.clone().unwrap_or_default(),
+                });
+            }
         }
 
-        Ok(file_bytes)
+        Ok((format, files))
     }
 
     /**
@@ -151,40 +425,491 @@ impl Artifact {
             .next()
     }
 
+    /**
+        Selects the artifact that is most compatible with the given system,
+        falling back to a partially compatible artifact if necessary.
+
+        This combines [`Artifact::sort_by_system_compatibility`] and
+        [`Artifact::find_partially_compatible_fallback`], but for any system.
+
+        # Errors
+
+        - If no artifact is compatible with the given system, even partially.
+    */
+    pub fn select_most_compatible(
+        artifacts: impl AsRef<[Self]>,
+        system: Descriptor,
+    ) -> RokitResult<Self> {
+        let artifacts = artifacts.as_ref();
+        if let Some(artifact) = sort_by_compatibility_with(artifacts, system, false)
+            .into_iter()
+            .next()
+        {
+            return Ok(artifact);
+        }
+
+        let artifact = sort_by_compatibility_with(artifacts, system, true)
+            .into_iter()
+            .next()
+            .ok_or(RokitError::NoCompatibleArtifact(system))?;
+        tracing::debug!(
+            name = %artifact.name.as_deref().unwrap_or("N/A"),
+            %system,
+            "found partially compatible fallback artifact",
+        );
+        Ok(artifact)
+    }
+
     fn sort_by_system_compatibility_inner(
         artifacts: impl AsRef<[Self]>,
         allow_partial_compatibility: bool,
     ) -> Vec<Self> {
-        let current_desc = Descriptor::current_system();
+        sort_by_compatibility_with(
+            artifacts.as_ref(),
+            Descriptor::current_system(),
+            allow_partial_compatibility,
+        )
+    }
+}
 
-        let mut compatible_artifacts = artifacts
-            .as_ref()
-            .iter()
-            .filter_map(|artifact| {
-                let name = artifact.name.as_deref()?;
-                if let Some(asset_desc) = Descriptor::detect(name) {
-                    let is_fully_compatible = current_desc.is_compatible_with(&asset_desc);
-                    let is_os_compatible = current_desc.os() == asset_desc.os();
-                    if is_fully_compatible || (allow_partial_compatibility && is_os_compatible) {
-                        Some((asset_desc, artifact))
-                    } else {
-                        None
-                    }
+/**
+    Checks if the given file name has the extension of a script
+    that Windows can run directly, such as `tool.cmd` or `tool.ps1`.
+*/
+fn is_windows_script(file_name: &str) -> bool {
+    Path::new(file_name)
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| {
+            WINDOWS_SCRIPT_EXTENSIONS
+                .iter()
+                .any(|script| ext.eq_ignore_ascii_case(script))
+        })
+}
+
+/**
+    Maps the contents of a plain file to the single desired file name.
+
+    Plain files can only ever be a single desired file, so this
+    returns an empty map if several desired file names are given.
+    Plain files never have a file mode, since downloads do not carry one.
+*/
+fn single_file(file_names: Vec<String>, contents: Vec<u8>) -> BTreeMap<String, ExtractedBinary> {
+    match <[String; 1]>::try_from(file_names) {
+        Ok([file_name]) => {
+            let binary = ExtractedBinary {
+                contents,
+                mode: None,
+            };
+            BTreeMap::from([(file_name, binary)])
+        }
+        Err(_) => BTreeMap::new(),
+    }
+}
+
+/**
+    Sorts the given artifacts by their compatibility with the given system,
+    filtering out any artifacts that are not compatible with it at all.
+*/
+fn sort_by_compatibility_with(
+    artifacts: &[Artifact],
+    system: Descriptor,
+    allow_partial_compatibility: bool,
+) -> Vec<Artifact> {
+    let mut compatible_artifacts = artifacts
+        .iter()
+        .filter_map(|artifact| {
+            let name = artifact.name.as_deref()?;
+            if let Some(asset_desc) = Descriptor::detect(name) {
+                let is_fully_compatible = system.is_compatible_with(&asset_desc);
+                let is_os_compatible = system.os() == asset_desc.os();
+                if is_fully_compatible || (allow_partial_compatibility && is_os_compatible) {
+                    Some((asset_desc, artifact))
                 } else {
                     None
                 }
-            })
+            } else {
+                None
+            }
+        })
+        .collect::<Vec<_>>();
+
+    compatible_artifacts.sort_by(|(desc_a, artifact_a), (desc_b, artifact_b)| {
+        system
+            .sort_by_preferred_compat(desc_a, desc_b)
+            .then_with(|| sort_preferred_artifact(artifact_a, artifact_b))
+    });
+
+    compatible_artifacts
+        .into_iter()
+        .map(|(_, artifact)| artifact.clone())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        env::consts::EXE_SUFFIX,
+        io::Write,
+        sync::{Arc, Mutex},
+    };
+
+    use flate2::{write::GzEncoder, Compression};
+
+    use crate::sources::sha256_hex;
+
+    use super::*;
+
+    const BINARY_CONTENTS: &[u8] = b"#!/bin/sh\necho tool\n";
+
+    fn new_artifact(name: &str) -> Artifact {
+        let (_, extensions) = split_filename_and_extensions(name);
+        Artifact {
+            provider: ArtifactProvider::GitHub,
+            format: ArtifactFormat::from_extensions(extensions),
+            id: None,
+            url: None,
+            name: Some(name.to_string()),
+            file_name: Some(name.to_string()),
+            tool_spec: "author/tool@1.0.0".parse().unwrap(),
+        }
+    }
+
+    fn build_tar() -> Vec<u8> {
+        let binary_name = format!("tool{EXE_SUFFIX}");
+        let mut builder = tar::Builder::new(Vec::new());
+        for (path, contents) in [
+            ("README.md", &b"# tool"[..]),
+            (binary_name.as_str(), BINARY_CONTENTS),
+        ] {
+            let mut header = tar::Header::new_gnu();
+            header.set_size(contents.len() as u64);
+            header.set_mode(0o755);
+            header.set_cksum();
+            builder.append_data(&mut header, path, contents).unwrap();
+        }
+        builder.into_inner().unwrap()
+    }
+
+    fn gzip(contents: &[u8]) -> Vec<u8> {
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(contents).unwrap();
+        encoder.finish().unwrap()
+    }
+
+    fn xz(contents: &[u8]) -> Vec<u8> {
+        let mut compressed = Vec::new();
+        lzma_rs::xz_compress(&mut &contents[..], &mut compressed).unwrap();
+        compressed
+    }
+
+    fn select(system: &str, names: &[&str]) -> RokitResult<String> {
+        let system = system.parse::<Descriptor>().unwrap();
+        let artifacts = names
+            .iter()
+            .map(|name| new_artifact(name))
             .collect::<Vec<_>>();
+        Artifact::select_most_compatible(artifacts, system).map(|a| a.name.unwrap())
+    }
 
-        compatible_artifacts.sort_by(|(desc_a, artifact_a), (desc_b, artifact_b)| {
-            current_desc
-                .sort_by_preferred_compat(desc_a, desc_b)
-                .then_with(|| sort_preferred_artifact(artifact_a, artifact_b))
-        });
+    #[test]
+    fn select_rust_style_names() {
+        const NAMES: &[&str] = &[
+            "tool-1.0.0-linux-aarch64.zip",
+            "tool-1.0.0-linux-x86_64.zip",
+            "tool-1.0.0-macos-aarch64.zip",
+            "tool-1.0.0-macos-x86_64.zip",
+            "tool-1.0.0-windows-x86_64.zip",
+        ];
+        let cases = [
+            ("linux-x86_64", "tool-1.0.0-linux-x86_64.zip"),
+            ("linux-aarch64", "tool-1.0.0-linux-aarch64.zip"),
+            ("macos-aarch64", "tool-1.0.0-macos-aarch64.zip"),
+            ("macos-x86_64", "tool-1.0.0-macos-x86_64.zip"),
+            ("windows-x86_64", "tool-1.0.0-windows-x86_64.zip"),
+        ];
+        for (system, expected) in cases {
+            assert_eq!(select(system, NAMES).unwrap(), expected, "{system}");
+        }
+    }
 
-        compatible_artifacts
-            .into_iter()
-            .map(|(_, artifact)| artifact.clone())
-            .collect()
+    #[test]
+    fn select_go_style_names() {
+        const NAMES: &[&str] = &[
+            "tool_1.0.0_darwin_amd64.tar.gz",
+            "tool_1.0.0_darwin_arm64.tar.gz",
+            "tool_1.0.0_linux_amd64.tar.gz",
+            "tool_1.0.0_linux_arm64.tar.gz",
+            "tool_1.0.0_windows_amd64.zip",
+            "tool_1.0.0_windows_arm64.zip",
+        ];
+        let cases = [
+            ("linux-x64", "tool_1.0.0_linux_amd64.tar.gz"),
+            ("linux-arm64", "tool_1.0.0_linux_arm64.tar.gz"),
+            ("macos-arm64", "tool_1.0.0_darwin_arm64.tar.gz"),
+            ("macos-x64", "tool_1.0.0_darwin_amd64.tar.gz"),
+            ("windows-x64", "tool_1.0.0_windows_amd64.zip"),
+            ("windows-arm64", "tool_1.0.0_windows_arm64.zip"),
+        ];
+        for (system, expected) in cases {
+            assert_eq!(select(system, NAMES).unwrap(), expected, "{system}");
+        }
+    }
+
+    #[test]
+    fn select_emulated_and_fallback() {
+        // Apple Silicon should fall back to Intel binaries
+        let names = ["tool-x64-macos.zip", "tool-x64-linux.zip"];
+        assert_eq!(select("macos-arm64", &names).unwrap(), "tool-x64-macos.zip");
+        // 64-bit Windows should fall back to 32-bit binaries
+        let names = ["tool-windows-i686.zip", "tool-x64-linux.zip"];
+        assert_eq!(
+            select("windows-x64", &names).unwrap(),
+            "tool-windows-i686.zip"
+        );
+        // Partially compatible artifacts should be used as a last resort
+        let names = ["tool-linux-arm32.zip", "tool-windows-x64.zip"];
+        assert_eq!(select("linux-x64", &names).unwrap(), "tool-linux-arm32.zip");
+    }
+
+    #[test]
+    fn select_linux_toolchain() {
+        const NAMES: &[&str] = &[
+            "tool-1.0.0-x86_64-unknown-linux-gnu.tar.gz",
+            "tool-1.0.0-x86_64-unknown-linux-musl.tar.gz",
+            "tool-1.0.0-aarch64-unknown-linux-musl.tar.gz",
+            "tool-1.0.0-x86_64-pc-windows-msvc.zip",
+        ];
+        // Hosts using glibc, or an unknown libc, should prefer gnu binaries
+        for system in ["linux-x64-gnu", "linux-x64"] {
+            assert_eq!(
+                select(system, NAMES).unwrap(),
+                "tool-1.0.0-x86_64-unknown-linux-gnu.tar.gz",
+                "{system}"
+            );
+        }
+        // Hosts using musl should prefer musl binaries
+        assert_eq!(
+            select("linux-x64-musl", NAMES).unwrap(),
+            "tool-1.0.0-x86_64-unknown-linux-musl.tar.gz"
+        );
+        // ... then unmarked binaries, and only then glibc binaries
+        let names = [
+            "tool-1.0.0-linux-x86_64-gnu.zip",
+            "tool-1.0.0-linux-x86_64.zip",
+        ];
+        assert_eq!(
+            select("linux-x64-musl", &names).unwrap(),
+            "tool-1.0.0-linux-x86_64.zip"
+        );
+        let names = ["tool-1.0.0-linux-x86_64-gnu.zip"];
+        assert_eq!(
+            select("linux-x64-musl", &names).unwrap(),
+            "tool-1.0.0-linux-x86_64-gnu.zip"
+        );
+    }
+
+    #[test]
+    fn select_none_compatible() {
+        let names = ["tool-windows-x64.zip", "tool-macos-arm64.zip", "README.md"];
+        assert!(matches!(
+            select("linux-x64", &names),
+            Err(RokitError::NoCompatibleArtifact(_))
+        ));
+        assert!(matches!(
+            select("linux-x64", &[]),
+            Err(RokitError::NoCompatibleArtifact(_))
+        ));
+    }
+
+    #[test]
+    fn version_from_tag() {
+        let version = |tag: &str| Release::version_from_tag(tag).to_string();
+        assert_eq!(version("v1.2.3"), "1.2.3");
+        assert_eq!(version("1.2.3"), "1.2.3");
+        assert_eq!(version("v2.0.0-rc1+build5"), "2.0.0-rc1+build5");
+        assert_eq!(version("nightly"), "0.0.0+nightly");
+        assert_eq!(version("release/2024_01"), "0.0.0+release-2024-01");
+    }
+
+    #[tokio::test]
+    async fn extract_plain_binary() {
+        // Truncated native header, which is not a recognized archive format,
+        // and also not parseable as a binary for a different system
+        let magic: &[u8] = match OS::current_system() {
+            OS::Linux => b"\x7fELF\x02\x01\x01\x00",
+            OS::MacOS => b"\xcf\xfa\xed\xfe\x07\x00",
+            OS::Windows => b"MZ\x90\x00",
+        };
+        let contents = [magic, b"binary contents"].concat();
+        let artifact = new_artifact("tool-1.0.0-linux-x86_64");
+        assert_eq!(artifact.format, None);
+        let extracted = artifact.extract_contents(contents.clone()).await.unwrap();
+        assert_eq!(extracted.contents, contents);
+    }
+
+    #[tokio::test]
+    async fn extract_tar_gz() {
+        let artifact = new_artifact("tool-1.0.0-linux-x86_64.tar.gz");
+        let contents = gzip(&build_tar());
+        let extracted = artifact.extract_contents(contents).await.unwrap();
+        assert_eq!(extracted.contents, BINARY_CONTENTS);
+    }
+
+    #[tokio::test]
+    async fn extract_tar_xz() {
+        let artifact = new_artifact("tool-1.0.0-linux-x86_64.tar.xz");
+        let contents = xz(&build_tar());
+        let extracted = artifact.extract_contents(contents).await.unwrap();
+        assert_eq!(extracted.contents, BINARY_CONTENTS);
+    }
+
+    #[tokio::test]
+    async fn extract_misnamed_archives() {
+        // Formats should be detected from the contents, not only the name
+        let artifact = new_artifact("tool-1.0.0-linux-x86_64.zip");
+        let extracted = artifact.extract_contents(xz(&build_tar())).await;
+        assert_eq!(extracted.unwrap().contents, BINARY_CONTENTS);
+
+        let artifact = new_artifact("tool-1.0.0-linux-x86_64");
+        let extracted = artifact.extract_contents(gzip(&build_tar())).await;
+        assert_eq!(extracted.unwrap().contents, BINARY_CONTENTS);
+    }
+
+    #[tokio::test]
+    async fn extract_gzip_single_file() {
+        // Bare gzip streams should be decompressed into the binary itself
+        let artifact = new_artifact("tool-1.0.0-linux-x86_64.gz");
+        let extracted = artifact.extract_contents(gzip(BINARY_CONTENTS)).await;
+        assert_eq!(extracted.unwrap().contents, BINARY_CONTENTS);
+        // ... even when misnamed as a tarball
+        let artifact = new_artifact("tool-1.0.0-linux-x86_64.tar.gz");
+        let extracted = artifact.extract_contents(gzip(BINARY_CONTENTS)).await;
+        assert_eq!(extracted.unwrap().contents, BINARY_CONTENTS);
+    }
+
+    #[tokio::test]
+    async fn extract_gzip_tar() {
+        // Gzipped tarballs should be unpacked, even when misnamed as a single file
+        let artifact = new_artifact("tool-1.0.0-linux-x86_64.gz");
+        let extracted = artifact.extract_contents(gzip(&build_tar())).await;
+        assert_eq!(extracted.unwrap().contents, BINARY_CONTENTS);
+    }
+
+    #[tokio::test]
+    async fn extract_multiple_binaries_missing() {
+        let artifact = new_artifact("tool-1.0.0-linux-x86_64.tar.gz");
+        let contents = gzip(&build_tar());
+        // Existing binaries should be extracted ...
+        let extracted = artifact.extract_binaries(contents.clone(), &["tool"]).await;
+        assert_eq!(extracted.unwrap()["tool"].contents, BINARY_CONTENTS);
+        // ... and missing binaries should all be listed in the error
+        let extracted = artifact
+            .extract_binaries(contents, &["tool", "helper", "daemon"])
+            .await;
+        match extracted {
+            Err(RokitError::Extract(err)) => match *err {
+                ExtractError::FilesMissing { file_names, .. } => {
+                    assert_eq!(file_names, ["helper", "daemon"]);
+                }
+                other => panic!("expected missing files, got {other:?}"),
+            },
+            other => panic!("expected extraction error, got {other:?}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn extract_with_progress() {
+        let reported = Arc::new(Mutex::new(Vec::new()));
+        let reported_inner = Arc::clone(&reported);
+        let artifact = new_artifact("tool-1.0.0-linux-x86_64.tar.gz");
+        let extracted = artifact
+            .extract_contents_with_progress(gzip(&build_tar()), move |processed, total| {
+                reported_inner.lock().unwrap().push((processed, total));
+            })
+            .await;
+        assert_eq!(extracted.unwrap().contents, BINARY_CONTENTS);
+        assert_eq!(*reported.lock().unwrap(), [(1, None), (2, None)]);
+    }
+
+    #[tokio::test]
+    async fn extract_with_checksum() {
+        let artifact = new_artifact("tool-1.0.0-linux-x86_64.tar.gz");
+        let contents = gzip(&build_tar());
+        let digest = sha256_hex(&contents);
+        let extracted = artifact
+            .extract_contents_with_checksum(contents, Some(digest))
+            .await;
+        assert_eq!(extracted.unwrap().contents, BINARY_CONTENTS);
+    }
+
+    #[tokio::test]
+    async fn extract_with_wrong_checksum() {
+        let artifact = new_artifact("tool-1.0.0-linux-x86_64.tar.gz");
+        let contents = gzip(&build_tar());
+        let digest = sha256_hex(b"something else entirely");
+        let extracted = artifact
+            .extract_contents_with_checksum(contents, Some(digest))
+            .await;
+        assert!(matches!(
+            extracted,
+            Err(RokitError::ChecksumMismatch { .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn extract_not_an_executable() {
+        // Error pages served in place of the actual binary should be rejected
+        let artifact = new_artifact("tool-1.0.0-linux-x86_64");
+        let page = b"<!DOCTYPE html><html><body>Not Found</body></html>".to_vec();
+        let extracted = artifact.extract_contents(page).await;
+        assert!(matches!(
+            extracted,
+            Err(RokitError::NotAnExecutable { file_name, archive_name })
+                if file_name == "tool" && archive_name == "tool-1.0.0-linux-x86_64"
+        ));
+
+        // ... including when they are inside of an archive
+        let mut builder = tar::Builder::new(Vec::new());
+        let mut header = tar::Header::new_gnu();
+        header.set_size(9);
+        header.set_mode(0o755);
+        header.set_cksum();
+        let binary_name = format!("tool{EXE_SUFFIX}");
+        builder
+            .append_data(&mut header, &binary_name, &b"Not Found"[..])
+            .unwrap();
+        let artifact = new_artifact("tool-1.0.0-linux-x86_64.tar.gz");
+        let extracted = artifact
+            .extract_contents(gzip(&builder.into_inner().unwrap()))
+            .await;
+        assert!(matches!(extracted, Err(RokitError::NotAnExecutable { .. })));
+    }
+
+    #[test]
+    fn windows_script_extensions() {
+        assert!(is_windows_script("tool.cmd"));
+        assert!(is_windows_script("tool.BAT"));
+        assert!(is_windows_script("tool-1.0.0-windows.ps1"));
+        assert!(!is_windows_script("tool"));
+        assert!(!is_windows_script("tool.exe"));
+        assert!(!is_windows_script("cmd"));
+    }
+
+    #[cfg(windows)]
+    #[tokio::test]
+    async fn extract_windows_scripts() {
+        // Scripts have no magic bytes, but should still be accepted on Windows
+        let script = b"@echo off\r\necho hello\r\n".to_vec();
+        for name in ["tool-1.0.0-windows.cmd", "tool-1.0.0-windows.BAT"] {
+            let artifact = new_artifact(name);
+            let extracted = artifact.extract_contents(script.clone()).await;
+            assert_eq!(extracted.unwrap(), script);
+        }
+
+        // ... but other files without any magic bytes should not be
+        let artifact = new_artifact("tool-1.0.0-windows.txt");
+        let extracted = artifact.extract_contents(script).await;
+        assert!(matches!(extracted, Err(RokitError::NotAnExecutable { .. })));
     }
 }
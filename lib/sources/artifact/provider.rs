@@ -5,10 +5,12 @@ use std::{fmt, str::FromStr};
 
     The default provider is [`ArtifactProvider::GitHub`].
 */
-#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub enum ArtifactProvider {
     #[default]
     GitHub,
+    GitLab,
+    Bitbucket,
 }
 
 impl ArtifactProvider {
@@ -16,6 +18,8 @@ impl ArtifactProvider {
     pub fn as_str(self) -> &'static str {
         match self {
             Self::GitHub => "github",
+            Self::GitLab => "gitlab",
+            Self::Bitbucket => "bitbucket",
         }
     }
 
@@ -23,6 +27,8 @@ impl ArtifactProvider {
     pub fn display_name(self) -> &'static str {
         match self {
             Self::GitHub => "GitHub",
+            Self::GitLab => "GitLab",
+            Self::Bitbucket => "Bitbucket",
         }
     }
 }
@@ -32,7 +38,9 @@ impl FromStr for ArtifactProvider {
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         let l = s.trim().to_lowercase();
         match l.as_str() {
-            "github" => Ok(Self::GitHub),
+            "github" | "gh" => Ok(Self::GitHub),
+            "gitlab" | "gl" => Ok(Self::GitLab),
+            "bitbucket" => Ok(Self::Bitbucket),
             _ => Err(format!("unknown artifact provider '{l}'")),
         }
     }
@@ -1,9 +1,18 @@
 use std::path::Path;
 
-const ALLOWED_EXTENSION_NAMES: [&str; 4] = ["zip", "tar", "gz", "tgz"];
+use crate::{
+    descriptor::{Arch, Toolchain, OS},
+    util::str::char_is_word_separator,
+};
+
+const ALLOWED_EXTENSION_NAMES: [&str; 7] = ["zip", "tar", "gz", "tgz", "xz", "txz", "7z"];
 const ALLOWED_EXTENSION_COUNT: usize = 2;
 
-pub(super) fn split_filename_and_extensions(name: &str) -> (&str, Vec<&str>) {
+// Words commonly found in target triples and asset names, that
+// do not describe any particular OS, architecture, or toolchain
+const PLATFORM_FILLER_WORDS: [&str; 3] = ["unknown", "pc", "universal"];
+
+pub(crate) fn split_filename_and_extensions(name: &str) -> (&str, Vec<&str>) {
     let mut path = Path::new(name);
     let mut exts = Vec::new();
 
@@ -34,10 +43,86 @@ pub(super) fn split_filename_and_extensions(name: &str) -> (&str, Vec<&str>) {
     (path, exts)
 }
 
+/**
+    Normalizes an asset file name by stripping any archive and executable
+    extensions, as well as all words that describe a platform or a version,
+    leaving only the base name of the tool that the asset belongs to.
+
+    For example, `rojo-7.4.1-linux-x86_64.zip` is normalized to `rojo`.
+
+    Words are removed using the same keywords as [`OS::detect`],
+    [`Arch::detect`] and [`Toolchain::detect`], and the returned
+    name may be empty if the asset name consisted only of such words.
+*/
+#[must_use]
+pub fn normalize_asset_name(name: &str) -> String {
+    let (name, _) = split_filename_and_extensions(name);
+    let name = match name.len().checked_sub(4) {
+        Some(idx) if name.is_char_boundary(idx) && name[idx..].eq_ignore_ascii_case(".exe") => {
+            &name[..idx]
+        }
+        _ => name,
+    };
+
+    // NOTE: Each part keeps its trailing separator, so that
+    // the remaining words are joined the same way as before
+    let normalized = name
+        .split_inclusive(char_is_word_separator)
+        .filter(|part| !is_platform_or_version_word(part.trim_end_matches(char_is_word_separator)))
+        .collect::<String>();
+
+    normalized.trim_matches(char_is_word_separator).to_string()
+}
+
+fn is_platform_or_version_word(word: &str) -> bool {
+    let version = word.strip_prefix(['v', 'V']).unwrap_or(word);
+    let is_version = version.starts_with(|c: char| c.is_ascii_digit())
+        && version.chars().all(|c| c.is_ascii_digit() || c == '.');
+    is_version
+        || PLATFORM_FILLER_WORDS
+            .iter()
+            .any(|filler| filler.eq_ignore_ascii_case(word))
+        || OS::detect(word).is_some()
+        || Arch::detect(word).is_some()
+        || Toolchain::detect(word).is_some()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn normalize_real_asset_names() {
+        const REAL_ASSETS: [(&str, &str); 14] = [
+            ("rojo-7.4.1-linux-x86_64.zip", "rojo"),
+            ("rojo-7.4.1-windows-x86_64.zip", "rojo"),
+            ("stylua-macos-aarch64.zip", "stylua"),
+            ("selene-0.27.1-linux.zip", "selene"),
+            ("selene-light-0.27.1-windows.zip", "selene-light"),
+            ("luau-lsp-win64.zip", "luau-lsp"),
+            ("wally-v0.3.2-linux.zip", "wally"),
+            ("lune-0.8.9-macos-x86_64.zip", "lune"),
+            ("darklua-linux-aarch64.zip", "darklua"),
+            ("ripgrep-14.1.0-x86_64-unknown-linux-musl.tar.gz", "ripgrep"),
+            ("just-1.31.0-aarch64-apple-darwin.tar.gz", "just"),
+            ("just-1.31.0-x86_64-pc-windows-msvc.zip", "just"),
+            ("sentry-cli-linux-i686-2.32.1.tgz", "sentry-cli"),
+            ("tool_name-armv7-unknown-linux-gnueabihf.exe", "tool_name"),
+        ];
+        for (asset_name, expected) in REAL_ASSETS {
+            assert_eq!(normalize_asset_name(asset_name), expected, "{asset_name}");
+        }
+    }
+
+    #[test]
+    fn normalize_keeps_unknown_words() {
+        assert_eq!(normalize_asset_name("tool"), "tool");
+        assert_eq!(normalize_asset_name("Tool.EXE"), "Tool");
+        assert_eq!(normalize_asset_name("tool.json"), "tool.json");
+        assert_eq!(normalize_asset_name("tool-cli-nightly"), "tool-cli-nightly");
+        assert_eq!(normalize_asset_name("linux-x86_64.zip"), "");
+    }
+
     #[test]
     fn split_filename_ext_basic() {
         assert_eq!(
@@ -0,0 +1,531 @@
+use std::collections::BTreeMap;
+
+use reqwest_middleware::ClientWithMiddleware;
+use semver::Version;
+use serde::de::DeserializeOwned;
+use tracing::{debug, instrument};
+
+use reqwest::{
+    header::{HeaderMap, HeaderValue, ACCEPT, AUTHORIZATION},
+    StatusCode,
+};
+
+use crate::tool::{ToolId, ToolSpec};
+
+use super::{
+    artifact::split_filename_and_extensions,
+    client::{
+        create_client, failed_status, rate_limit_reset, read_body_with_progress, ClientOptions,
+    },
+    Artifact, ArtifactProvider, Release,
+};
+
+const BASE_URL: &str = "https://api.bitbucket.org/2.0";
+
+// NOTE: This is the maximum page size allowed by the Bitbucket API
+const PAGE_SIZE: usize = 100;
+
+pub mod models;
+mod result;
+
+use self::models::{BitbucketDownload, BitbucketDownloadsPage};
+
+pub use self::result::{BitbucketError, BitbucketResult};
+
+/**
+    A provider for artifacts uploaded to the downloads of Bitbucket repositories.
+
+    Unlike GitHub and GitLab, downloads on Bitbucket are not associated with
+    releases or tags, so the version of each download is instead parsed from
+    its file name, such as `tool-1.2.3-linux-x86_64.zip`, and all downloads
+    with the same version are treated as the artifacts of a single release.
+*/
+#[derive(Debug, Clone)]
+pub struct BitbucketProvider {
+    client: ClientWithMiddleware,
+    base_url: String,
+    has_auth: bool,
+}
+
+impl BitbucketProvider {
+    fn new_inner(pat: Option<String>, options: &ClientOptions) -> BitbucketResult<Self> {
+        let has_auth = pat.is_some();
+        let headers = {
+            let mut headers = HeaderMap::new();
+            if let Some(pat) = pat {
+                let token = format!("Bearer {pat}");
+                headers.insert(AUTHORIZATION, HeaderValue::from_str(&token)?);
+            }
+            headers
+        };
+
+        let client = create_client(headers, options)?;
+        let base_url = options
+            .base_url_or(ArtifactProvider::Bitbucket, BASE_URL)
+            .to_string();
+
+        Ok(Self {
+            client,
+            base_url,
+            has_auth,
+        })
+    }
+
+    async fn get_json<T: DeserializeOwned>(&self, url: &str) -> BitbucketResult<T> {
+        let response = self
+            .client
+            .get(url)
+            .header(ACCEPT, "application/json")
+            .send()
+            .await?;
+        if let Some(reset_at) = rate_limit_reset(response.status(), response.headers()) {
+            return Err(BitbucketError::RateLimited { reset_at });
+        }
+        if let Some((status, url)) = failed_status(&response) {
+            return Err(BitbucketError::Http { status, url });
+        }
+        Ok(response.json().await?)
+    }
+
+    async fn get_bytes(
+        &self,
+        url: &str,
+        progress: &mut (dyn FnMut(u64, Option<u64>) + Send),
+    ) -> BitbucketResult<Vec<u8>> {
+        let response = self
+            .client
+            .get(url)
+            .header(ACCEPT, HeaderValue::from_static("application/octet-stream"))
+            .send()
+            .await?;
+        if let Some(reset_at) = rate_limit_reset(response.status(), response.headers()) {
+            return Err(BitbucketError::RateLimited { reset_at });
+        }
+        if let Some((status, url)) = failed_status(&response) {
+            return Err(BitbucketError::Http { status, url });
+        }
+        Ok(read_body_with_progress(response, progress).await?)
+    }
+
+    /**
+        Creates a new Bitbucket source instance.
+
+        # Errors
+
+        - If the Bitbucket API client could not be created.
+    */
+    pub fn new() -> BitbucketResult<Self> {
+        Self::new_inner(None, &ClientOptions::default())
+    }
+
+    /**
+        Creates a new authenticated Bitbucket source instance with an access token.
+
+        Note that this does not verify the formatting or validity of the token,
+        use the `verify_authentication` method for checking with the Bitbucket API.
+
+        # Errors
+
+        - If the Bitbucket API client could not be created.
+    */
+    pub fn new_authenticated(pat: impl AsRef<str>) -> BitbucketResult<Self> {
+        let pat: String = pat.as_ref().trim().to_string();
+        Self::new_inner(Some(pat), &ClientOptions::default())
+    }
+
+    /**
+        Creates a new Bitbucket source instance with the given client
+        options, and optionally authenticated with an access token.
+
+        # Errors
+
+        - If the Bitbucket API client could not be created.
+    */
+    pub fn new_with_options(pat: Option<&str>, options: &ClientOptions) -> BitbucketResult<Self> {
+        let pat = pat.map(|pat| pat.trim().to_string());
+        Self::new_inner(pat, options)
+    }
+
+    /**
+        Verifies that the current authentication token is valid.
+
+        Returns `true` if the token is valid, `false` if it is not.
+
+        Always returns `false` if the source is not authenticated.
+
+        # Errors
+
+        - If the request to the Bitbucket API failed.
+    */
+    pub async fn verify_authentication(&self) -> BitbucketResult<bool> {
+        if !self.has_auth {
+            return Ok(false);
+        }
+
+        let url = format!("{}/user", self.base_url);
+        let res = self.get_json::<serde_json::Value>(&url).await;
+
+        match res {
+            Ok(_) => Ok(true),
+            Err(e) if is_unauthenticated(&e) => Ok(false),
+            Err(e) => Err(e),
+        }
+    }
+
+    /**
+        Fetches all downloads for a given tool, grouped by the
+        version parsed from their file names, in ascending order.
+
+        Downloads without a version in their file name are skipped.
+    */
+    async fn get_downloads_by_version(
+        &self,
+        tool_id: &ToolId,
+    ) -> BitbucketResult<BTreeMap<Version, Vec<BitbucketDownload>>> {
+        let mut url = format!(
+            "{base_url}/repositories/{workspace}/{repo}/downloads?pagelen={PAGE_SIZE}",
+            base_url = self.base_url,
+            workspace = tool_id.author(),
+            repo = tool_id.name(),
+        );
+
+        let mut downloads = BTreeMap::<Version, Vec<BitbucketDownload>>::new();
+        loop {
+            let page: BitbucketDownloadsPage = match self.get_json(&url).await {
+                Err(e) if is_404(&e) => {
+                    return Err(BitbucketError::LatestReleaseNotFound(
+                        tool_id.clone().into(),
+                    ));
+                }
+                Err(e) => return Err(e),
+                Ok(p) => p,
+            };
+
+            for download in page.values {
+                if let Some(version) = version_from_download_name(&download.name) {
+                    downloads.entry(version).or_default().push(download);
+                }
+            }
+
+            match page.next {
+                Some(next) => url = next.to_string(),
+                None => break,
+            }
+        }
+
+        Ok(downloads)
+    }
+
+    /**
+        Fetches the latest release for a given tool, which contains
+        all downloads with the highest version in their file names.
+
+        Prerelease versions are never considered to be the latest release.
+    */
+    #[instrument(skip(self), fields(%tool_id), level = "debug")]
+    pub async fn get_latest_release(&self, tool_id: &ToolId) -> BitbucketResult<Release> {
+        debug!(id = %tool_id, "fetching latest release for tool");
+
+        let downloads = self.get_downloads_by_version(tool_id).await?;
+        let Some((version, downloads)) = downloads
+            .into_iter()
+            .rev()
+            .find(|(version, _)| version.pre.is_empty())
+        else {
+            return Err(BitbucketError::LatestReleaseNotFound(
+                tool_id.clone().into(),
+            ));
+        };
+
+        let tool_spec: ToolSpec = (tool_id.clone(), version).into();
+        Ok(Release {
+            changelog: None,
+            artifacts: artifacts_from_downloads(&downloads, &tool_spec),
+        })
+    }
+
+    /**
+        Fetches all published versions for a given tool, in descending order.
+
+        Downloads with file names that do not contain a version are skipped.
+    */
+    #[instrument(skip(self), fields(%tool_id), level = "debug")]
+    pub async fn list_versions(&self, tool_id: &ToolId) -> BitbucketResult<Vec<Version>> {
+        debug!(id = %tool_id, "fetching all versions for tool");
+
+        let downloads = match self.get_downloads_by_version(tool_id).await {
+            Err(BitbucketError::LatestReleaseNotFound(_)) => {
+                return Err(BitbucketError::ToolNotFound(tool_id.clone().into()));
+            }
+            Err(e) => return Err(e),
+            Ok(d) => d,
+        };
+        Ok(downloads.into_keys().rev().collect())
+    }
+
+    /**
+        Fetches a specific release for a given tool, which contains
+        all downloads with the given version in their file names.
+    */
+    #[instrument(skip(self), fields(%tool_spec), level = "debug")]
+    pub async fn get_specific_release(&self, tool_spec: &ToolSpec) -> BitbucketResult<Release> {
+        debug!(spec = %tool_spec, "fetching release for tool");
+
+        let mut downloads = match self.get_downloads_by_version(tool_spec.id()).await {
+            Err(BitbucketError::LatestReleaseNotFound(_)) => {
+                return Err(BitbucketError::ReleaseNotFound(tool_spec.clone().into()));
+            }
+            Err(e) => return Err(e),
+            Ok(d) => d,
+        };
+        let Some(downloads) = downloads.remove(tool_spec.version()) else {
+            return Err(BitbucketError::ReleaseNotFound(tool_spec.clone().into()));
+        };
+
+        Ok(Release {
+            changelog: None,
+            artifacts: artifacts_from_downloads(&downloads, tool_spec),
+        })
+    }
+
+    /**
+        Fetches a release for a given tool by its tag name.
+
+        Since downloads are not associated with tags on Bitbucket,
+        the tag must be a version, optionally prefixed with a `v`.
+    */
+    #[instrument(skip(self), fields(%tool_id), level = "debug")]
+    pub async fn get_release_by_tag(
+        &self,
+        tool_id: &ToolId,
+        tag: &str,
+    ) -> BitbucketResult<Release> {
+        debug!(id = %tool_id, tag, "fetching release by tag for tool");
+
+        let not_found = || BitbucketError::ReleaseTagNotFound {
+            tag: tag.to_string(),
+        };
+
+        let version = tag
+            .trim_start_matches('v')
+            .parse::<Version>()
+            .map_err(|_| not_found())?;
+        let tool_spec: ToolSpec = (tool_id.clone(), version).into();
+
+        match self.get_specific_release(&tool_spec).await {
+            Err(BitbucketError::ReleaseNotFound(_)) => Err(not_found()),
+            res => res,
+        }
+    }
+
+    /**
+        Downloads the contents of the given artifact.
+
+        # Errors
+
+        - If the request to the Bitbucket API failed.
+    */
+    pub async fn download_artifact_contents(
+        &self,
+        artifact: &Artifact,
+    ) -> BitbucketResult<Vec<u8>> {
+        self.download_artifact_contents_with_progress(artifact, |_, _| {})
+            .await
+    }
+
+    /**
+        Downloads the contents of the given artifact, calling the given callback
+        with the number of bytes downloaded so far, and the total size, if known.
+
+        # Errors
+
+        - If the request to the Bitbucket API failed.
+    */
+    #[instrument(skip(self, artifact, progress), level = "debug")]
+    pub async fn download_artifact_contents_with_progress(
+        &self,
+        artifact: &Artifact,
+        mut progress: impl FnMut(u64, Option<u64>) + Send,
+    ) -> BitbucketResult<Vec<u8>> {
+        assert_eq!(
+            artifact.provider,
+            ArtifactProvider::Bitbucket,
+            "artifact must be from Bitbucket"
+        );
+
+        let url = artifact
+            .url
+            .as_ref()
+            .expect("Bitbucket artifacts have urls");
+        debug!(%url, "downloading artifact contents");
+
+        // NOTE: Bitbucket responds with a redirect to the actual
+        // file contents, which our client follows automatically
+        self.get_bytes(url.as_str(), &mut progress).await
+    }
+}
+
+/**
+    Parses the version from the file name of a download, which is the first
+    `-` or `_` separated part of the name that starts with a version number,
+    optionally prefixed with a `v` - such as `tool-v1.2.3-linux-x86_64.zip`.
+
+    A prerelease is also included, if it directly follows the version in its
+    own part of the name, and consists of a known prerelease identifier.
+*/
+fn version_from_download_name(name: &str) -> Option<Version> {
+    // NOTE: Archive extensions must be stripped first, to not end
+    // up as part of any prerelease directly before them - `rc.1.zip`
+    let (name, _) = split_filename_and_extensions(name);
+    let parts = name.split(['-', '_']).collect::<Vec<_>>();
+    parts.iter().enumerate().find_map(|(index, part)| {
+        let part = part.strip_prefix(['v', 'V']).unwrap_or(part);
+        let mut numbers = part.split('.').map_while(|n| n.parse::<u64>().ok());
+        let (major, minor, patch) = (numbers.next()?, numbers.next()?, numbers.next()?);
+
+        let mut version = Version::new(major, minor, patch);
+        let pre = parts.get(index + 1).filter(|pre| {
+            let identifier = pre.split('.').next().unwrap_or_default();
+            ["alpha", "beta", "rc", "pre"]
+                .iter()
+                .any(|known| known.eq_ignore_ascii_case(identifier))
+        });
+        if let Some(pre) = pre.and_then(|pre| semver::Prerelease::new(pre).ok()) {
+            version.pre = pre;
+        }
+        Some(version)
+    })
+}
+
+fn is_404(err: &BitbucketError) -> bool {
+    matches!(err, BitbucketError::Http { status, .. } if *status == StatusCode::NOT_FOUND)
+}
+
+fn is_unauthenticated(err: &BitbucketError) -> bool {
+    matches!(
+        err,
+        BitbucketError::Http { status, .. }
+            if *status == StatusCode::UNAUTHORIZED || *status == StatusCode::FORBIDDEN
+    )
+}
+
+fn artifacts_from_downloads(downloads: &[BitbucketDownload], spec: &ToolSpec) -> Vec<Artifact> {
+    downloads
+        .iter()
+        .map(|download| Artifact::from_bitbucket_download(download, spec))
+        .collect::<Vec<_>>()
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+    use wiremock::{
+        matchers::{method, path, query_param},
+        Mock, MockServer, ResponseTemplate,
+    };
+
+    use crate::{result::RokitError, sources::test_options};
+
+    use super::*;
+
+    fn download(server: &MockServer, name: &str) -> serde_json::Value {
+        let href = format!("{}/repositories/author/name/downloads/{name}", server.uri());
+        json!({ "name": name, "links": { "self": { "href": href } } })
+    }
+
+    #[test]
+    fn versions_from_download_names() {
+        let version = |name: &str| version_from_download_name(name).map(|v| v.to_string());
+        assert_eq!(
+            version("tool-1.2.3-linux-x86_64.zip").as_deref(),
+            Some("1.2.3")
+        );
+        assert_eq!(version("tool-v1.2.3.tar.gz").as_deref(), Some("1.2.3"));
+        assert_eq!(version("tool_2.0.0_windows.zip").as_deref(), Some("2.0.0"));
+        assert_eq!(
+            version("tool-2.0.0-rc.1-macos.zip").as_deref(),
+            Some("2.0.0-rc.1")
+        );
+        assert_eq!(
+            version("tool-2.0.0-rc.1.zip").as_deref(),
+            Some("2.0.0-rc.1")
+        );
+        assert_eq!(
+            version("tool-2.0.0-beta.2.tar.gz").as_deref(),
+            Some("2.0.0-beta.2")
+        );
+        assert_eq!(version("tool-linux.zip"), None);
+        assert_eq!(version("tool-1.2-linux.zip"), None);
+    }
+
+    #[tokio::test]
+    async fn lists_and_fetches_downloads() {
+        let server = MockServer::start().await;
+        let next = format!(
+            "{}/repositories/author/name/downloads?pagelen=100&page=2",
+            server.uri()
+        );
+        Mock::given(method("GET"))
+            .and(path("/repositories/author/name/downloads"))
+            .and(query_param("page", "2"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "values": [
+                    download(&server, "name-1.0.0-linux-x86_64.zip"),
+                    download(&server, "README.txt"),
+                ],
+            })))
+            .with_priority(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/repositories/author/name/downloads"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "values": [
+                    download(&server, "name-1.1.0-linux-x86_64.zip"),
+                    download(&server, "name-1.1.0-windows-x86_64.zip"),
+                    download(&server, "name-2.0.0-rc.1-linux-x86_64.zip"),
+                ],
+                "next": next,
+            })))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path(
+                "/repositories/author/name/downloads/name-1.1.0-linux-x86_64.zip",
+            ))
+            .respond_with(ResponseTemplate::new(200).set_body_bytes(b"zip contents".to_vec()))
+            .mount(&server)
+            .await;
+
+        let options = test_options(ArtifactProvider::Bitbucket, server.uri());
+        let provider = BitbucketProvider::new_with_options(None, &options).unwrap();
+        let id = "bitbucket:author/name".parse::<ToolId>().unwrap();
+
+        let versions = provider.list_versions(&id).await.unwrap();
+        let versions = versions.iter().map(ToString::to_string).collect::<Vec<_>>();
+        assert_eq!(versions, ["2.0.0-rc.1", "1.1.0", "1.0.0"]);
+
+        // The latest release should skip prereleases, and group downloads
+        let release = provider.get_latest_release(&id).await.unwrap();
+        assert_eq!(release.artifacts.len(), 2);
+        let artifact = &release.artifacts[0];
+        assert_eq!(artifact.provider, ArtifactProvider::Bitbucket);
+        assert_eq!(artifact.tool_spec.version(), &Version::new(1, 1, 0));
+
+        let contents = provider.download_artifact_contents(artifact).await.unwrap();
+        assert_eq!(contents, b"zip contents");
+
+        let spec = "bitbucket:author/name@1.0.0".parse::<ToolSpec>().unwrap();
+        let release = provider.get_specific_release(&spec).await.unwrap();
+        assert_eq!(release.artifacts.len(), 1);
+
+        let err = provider
+            .get_release_by_tag(&id, "v3.0.0")
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            RokitError::from(err),
+            RokitError::ReleaseNotFound { tag } if tag == "v3.0.0"
+        ));
+    }
+}
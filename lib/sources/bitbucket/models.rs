@@ -0,0 +1,26 @@
+use serde::Deserialize;
+use url::Url;
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct BitbucketDownloadsPage {
+    #[serde(default)]
+    pub values: Vec<BitbucketDownload>,
+    pub next: Option<Url>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct BitbucketDownload {
+    pub name: String,
+    pub links: BitbucketDownloadLinks,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct BitbucketDownloadLinks {
+    #[serde(rename = "self")]
+    pub self_link: BitbucketLink,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct BitbucketLink {
+    pub href: Url,
+}
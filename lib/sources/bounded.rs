@@ -0,0 +1,92 @@
+use std::{future::Future, sync::Arc};
+
+use futures::future::join_all;
+use tokio::sync::Semaphore;
+
+/**
+    The default maximum number of requests that may be in flight at once,
+    when fetching information about many tools in parallel.
+*/
+pub const DEFAULT_MAX_IN_FLIGHT: usize = 8;
+
+/**
+    Runs the given async function for each of the given items, with at most
+    `max_in_flight` of them running at once, and collects all of their results.
+
+    Results are returned in the same order as the given items, and a limit
+    of zero is treated as a limit of one, to always make progress.
+*/
+pub(crate) async fn run_bounded<T, R, F, Fut>(
+    items: impl IntoIterator<Item = T>,
+    max_in_flight: usize,
+    f: F,
+) -> Vec<(T, R)>
+where
+    T: Clone,
+    F: Fn(T) -> Fut,
+    Fut: Future<Output = R>,
+{
+    let semaphore = Arc::new(Semaphore::new(max_in_flight.max(1)));
+    let futs = items.into_iter().map(|item| {
+        let semaphore = Arc::clone(&semaphore);
+        let fut = f(item.clone());
+        async move {
+            // NOTE: The semaphore is never closed, so acquiring can not fail
+            let _permit = semaphore.acquire().await.expect("semaphore is open");
+            (item, fut.await)
+        }
+    });
+    join_all(futs).await
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        sync::atomic::{AtomicUsize, Ordering},
+        time::Duration,
+    };
+
+    use tokio::time::sleep;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn limits_in_flight() {
+        let in_flight = AtomicUsize::new(0);
+        let max_seen = AtomicUsize::new(0);
+
+        let results = run_bounded(0..32, 4, |item| {
+            let in_flight = &in_flight;
+            let max_seen = &max_seen;
+            async move {
+                let current = in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+                max_seen.fetch_max(current, Ordering::SeqCst);
+                sleep(Duration::from_millis(5)).await;
+                in_flight.fetch_sub(1, Ordering::SeqCst);
+                if item % 3 == 0 {
+                    Err(item)
+                } else {
+                    Ok(item * 2)
+                }
+            }
+        })
+        .await;
+
+        assert_eq!(max_seen.load(Ordering::SeqCst), 4);
+        assert_eq!(results.len(), 32);
+        // Failures should not abort the remaining items, and order is kept
+        for (item, result) in results {
+            if item % 3 == 0 {
+                assert_eq!(result, Err(item));
+            } else {
+                assert_eq!(result, Ok(item * 2));
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn zero_limit_makes_progress() {
+        let results = run_bounded(["a", "b"], 0, |item| async move { item.len() }).await;
+        assert_eq!(results, [("a", 1), ("b", 1)]);
+    }
+}
@@ -0,0 +1,144 @@
+use std::fmt::Write;
+
+use sha2::{Digest, Sha256};
+
+use crate::result::{RokitError, RokitResult};
+
+/**
+    Computes the SHA256 digest of the given contents, as a lowercase hex string.
+*/
+#[must_use]
+pub fn sha256_hex(contents: impl AsRef<[u8]>) -> String {
+    let digest = Sha256::digest(contents.as_ref());
+    digest.iter().fold(String::new(), |mut hex, byte| {
+        let _ = write!(hex, "{byte:02x}");
+        hex
+    })
+}
+
+/**
+    Verifies that the SHA256 digest of the given contents matches the expected
+    hex digest. Verification is skipped if no expected digest is given.
+
+    # Errors
+
+    - If the digest of the contents does not match the expected digest.
+*/
+pub fn verify_sha256(contents: impl AsRef<[u8]>, expected: Option<String>) -> RokitResult<()> {
+    let Some(expected) = expected else {
+        return Ok(());
+    };
+
+    let expected = expected.trim().to_ascii_lowercase();
+    let actual = sha256_hex(contents);
+    if expected == actual {
+        tracing::trace!(digest = %actual, "verified checksum");
+        Ok(())
+    } else {
+        Err(RokitError::ChecksumMismatch { expected, actual })
+    }
+}
+
+/**
+    Finds the expected SHA256 digest for a file in the contents of a checksum file.
+
+    Supports both single-digest files (typically named `<file>.sha256`), and
+    checksum lists in the format written by `sha256sum` (`checksums.txt`).
+
+    Digests that are listed for a different file name are never returned.
+*/
+#[must_use]
+pub fn find_sha256_in_checksums(checksums: &str, file_name: &str) -> Option<String> {
+    let is_digest = |s: &str| s.len() == 64 && s.chars().all(|c| c.is_ascii_hexdigit());
+
+    let mut lines = checksums.lines().map(str::trim).filter(|l| !l.is_empty());
+    let listed = lines.clone().find_map(|line| {
+        let (digest, name) = line.split_once(char::is_whitespace)?;
+        // NOTE: sha256sum prefixes names with '*' when in binary mode
+        let name = name.trim().trim_start_matches('*');
+        (is_digest(digest) && name == file_name).then(|| digest.to_ascii_lowercase())
+    });
+
+    // NOTE: Single digests that list a file name were already handled
+    // above, if it matched, so only digests without any name remain
+    listed.or_else(|| {
+        let single = lines.next()?;
+        let rest_is_empty = lines.next().is_none();
+        let mut parts = single.split_whitespace();
+        let digest = parts.next()?;
+        let has_name = parts.next().is_some();
+        (rest_is_empty && !has_name && is_digest(digest)).then(|| digest.to_ascii_lowercase())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HELLO_DIGEST: &str = "2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824";
+
+    #[test]
+    fn digest_known_value() {
+        assert_eq!(sha256_hex(b"hello"), HELLO_DIGEST);
+    }
+
+    #[test]
+    fn verify_matching() {
+        assert!(verify_sha256(b"hello", Some(HELLO_DIGEST.to_string())).is_ok());
+        assert!(verify_sha256(b"hello", Some(HELLO_DIGEST.to_uppercase())).is_ok());
+        assert!(verify_sha256(b"hello", Some(format!(" {HELLO_DIGEST}\n"))).is_ok());
+    }
+
+    #[test]
+    fn verify_skipped() {
+        assert!(verify_sha256(b"hello", None).is_ok());
+    }
+
+    #[test]
+    fn verify_mismatch() {
+        let wrong = sha256_hex(b"goodbye");
+        match verify_sha256(b"hello", Some(wrong.clone())) {
+            Err(RokitError::ChecksumMismatch { expected, actual }) => {
+                assert_eq!(expected, wrong);
+                assert_eq!(actual, HELLO_DIGEST);
+            }
+            other => panic!("expected checksum mismatch, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn find_in_checksums() {
+        let other = sha256_hex(b"other");
+        let list = format!(
+            "{other}  tool-linux.zip\n{HELLO_DIGEST} *tool-macos.zip\n{other}  tool-windows.zip\n"
+        );
+        assert_eq!(
+            find_sha256_in_checksums(&list, "tool-macos.zip").as_deref(),
+            Some(HELLO_DIGEST)
+        );
+        assert_eq!(
+            find_sha256_in_checksums(&list, "tool-linux.zip"),
+            Some(other)
+        );
+        assert_eq!(find_sha256_in_checksums(&list, "tool-freebsd.zip"), None);
+        // Single-digest files may or may not list the file name
+        let single = format!("{HELLO_DIGEST}\n");
+        assert_eq!(
+            find_sha256_in_checksums(&single, "tool.zip").as_deref(),
+            Some(HELLO_DIGEST)
+        );
+        let single = format!("{HELLO_DIGEST} *tool.zip\n");
+        assert_eq!(
+            find_sha256_in_checksums(&single, "tool.zip").as_deref(),
+            Some(HELLO_DIGEST)
+        );
+        assert_eq!(find_sha256_in_checksums("not a digest", "tool.zip"), None);
+    }
+
+    #[test]
+    fn find_single_for_other_file() {
+        // Single digests for a differently named file should never be used
+        let single = format!("{HELLO_DIGEST}  other-asset.zip\n");
+        assert_eq!(find_sha256_in_checksums(&single, "tool.zip"), None);
+    }
+}
@@ -1,38 +1,238 @@
-use std::time::Duration;
+use std::{
+    collections::BTreeMap,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
 
+use async_trait::async_trait;
+use http::Extensions;
+use percent_encoding::{utf8_percent_encode, AsciiSet, CONTROLS};
 use reqwest::{
-    header::{HeaderMap, USER_AGENT},
-    Client, Error,
+    header::{HeaderMap, HeaderName, RETRY_AFTER, USER_AGENT},
+    Client, Error, NoProxy, Proxy, Request, Response, StatusCode,
 };
 
-use reqwest_middleware::{ClientBuilder, ClientWithMiddleware};
-use reqwest_retry::{policies::ExponentialBackoff, RetryTransientMiddleware};
+use reqwest_middleware::{ClientBuilder, ClientWithMiddleware, Middleware, Next};
+use reqwest_retry::{
+    default_on_request_failure, default_on_request_success, policies::ExponentialBackoff,
+    RetryTransientMiddleware, Retryable, RetryableStrategy,
+};
 use reqwest_tracing::TracingMiddleware;
+use tokio::time::sleep;
+use tracing::debug;
+
+use super::ArtifactProvider;
+
+const DEFAULT_MAX_RETRIES: u32 = 3;
+const CONNECT_TIMEOUT_SECS: u64 = 15;
+const REQUEST_TIMEOUT_SECS: u64 = 60;
+
+// NOTE: This is the path segment set from the URL standard,
+// with the addition of `%`, so that segments are always escaped
+const PATH_SEGMENT: &AsciiSet = &CONTROLS
+    .add(b' ')
+    .add(b'"')
+    .add(b'#')
+    .add(b'%')
+    .add(b'/')
+    .add(b'<')
+    .add(b'>')
+    .add(b'?')
+    .add(b'`')
+    .add(b'{')
+    .add(b'}');
+
+/**
+    Options for the HTTP clients used by artifact providers.
+*/
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClientOptions {
+    /**
+        The maximum number of times to retry a request that failed
+        due to a transient error, such as a connection reset or a
+        5xx server error. Requests are retried with exponential backoff.
+
+        Requests that failed for deterministic reasons, such
+        as a `404 Not Found` response, are never retried.
+    */
+    pub max_retries: u32,
+    /**
+        Custom API base URLs for providers, such as a self-hosted
+        GitHub Enterprise instance at `https://github.mycorp.com/api/v3`.
+
+        Providers without a custom base URL use their public API.
+    */
+    pub base_urls: BTreeMap<ArtifactProvider, String>,
+    /**
+        An explicit proxy URL to use for all requests, such as
+        `http://proxy.mycorp.com:8080`, which takes precedence over
+        the `HTTPS_PROXY` and `HTTP_PROXY` environment variables.
+
+        Hosts listed in the `NO_PROXY` environment variable are
+        never proxied, regardless of how the proxy was configured.
+    */
+    pub proxy: Option<String>,
+    /**
+        The maximum total time to wait for rate limits to reset, when a
+        request is rejected due to rate limiting and the response says
+        when the limit resets, using a `Retry-After` or rate limit header.
+
+        Requests are retried once the rate limit has reset, and if waiting
+        would exceed this bound, the rate limited response is returned as-is.
+
+        Disabled by default, so that rate limited requests fail fast.
+    */
+    pub rate_limit_max_wait: Option<Duration>,
+    /**
+        Whether to allow requests over plain HTTP, such as to a
+        local mock server that does not support HTTPS.
+
+        Disabled by default, so that all requests must use HTTPS.
+    */
+    pub allow_http: bool,
+}
+
+impl ClientOptions {
+    /**
+        Returns the API base URL to use for the given provider,
+        falling back to the given default if none was configured.
+
+        Trailing slashes are stripped from the returned URL.
+    */
+    #[must_use]
+    pub fn base_url_or<'a>(&'a self, provider: ArtifactProvider, default: &'a str) -> &'a str {
+        self.base_urls
+            .get(&provider)
+            .map_or(default, String::as_str)
+            .trim_end_matches('/')
+    }
+}
+
+impl Default for ClientOptions {
+    fn default() -> Self {
+        Self {
+            max_retries: DEFAULT_MAX_RETRIES,
+            base_urls: BTreeMap::new(),
+            proxy: None,
+            rate_limit_max_wait: None,
+            allow_http: false,
+        }
+    }
+}
+
+/**
+    Creates client options for tests, which use the given mock
+    server URI as the base URL for the given provider.
+*/
+#[cfg(test)]
+pub(crate) fn test_options(provider: ArtifactProvider, uri: String) -> ClientOptions {
+    let mut options = ClientOptions {
+        allow_http: true,
+        ..ClientOptions::default()
+    };
+    options.base_urls.insert(provider, uri);
+    options
+}
+
+/**
+    Middleware that waits for rate limits to reset, and then retries
+    the request, for up to a bounded total amount of time.
+*/
+struct RateLimitWaitMiddleware {
+    max_wait: Duration,
+}
+
+#[async_trait]
+impl Middleware for RateLimitWaitMiddleware {
+    async fn handle(
+        &self,
+        req: Request,
+        extensions: &mut Extensions,
+        next: Next<'_>,
+    ) -> reqwest_middleware::Result<Response> {
+        let mut waited = Duration::ZERO;
+        let mut req = req;
+        loop {
+            // NOTE: Requests with streaming bodies can not be cloned, and retried
+            let Some(retry_req) = req.try_clone() else {
+                return next.run(req, extensions).await;
+            };
+
+            let response = next.clone().run(req, extensions).await?;
+            let Some(wait) = rate_limit_wait(response.status(), response.headers()) else {
+                return Ok(response);
+            };
+            if waited + wait > self.max_wait {
+                return Ok(response);
+            }
+
+            debug!(?wait, url = %response.url(), "waiting for rate limit to reset");
+            sleep(wait).await;
+            waited += wait;
+            req = retry_req;
+        }
+    }
+}
+
+/*
+    Retry strategy used when waiting for rate limits is enabled, which never
+    retries rate limited responses, since any response that gets here has
+    already been waited on for as long as the options allow.
+*/
+struct RateLimitAwareStrategy;
+
+impl RetryableStrategy for RateLimitAwareStrategy {
+    fn handle(&self, res: &reqwest_middleware::Result<Response>) -> Option<Retryable> {
+        match res {
+            Ok(response) if rate_limit_reset(response.status(), response.headers()).is_some() => {
+                Some(Retryable::Fatal)
+            }
+            Ok(response) => default_on_request_success(response),
+            Err(error) => default_on_request_failure(error),
+        }
+    }
+}
 
 /*
     Adds middleware for:
 
     - Retrying failed requests with exponential backoff
+    - Waiting for rate limits to reset, if enabled in the options
     - Tracing of HTTP requests
+
+    NOTE: Rate limits are waited for inside of the retry middleware,
+    so that rate limited responses are never blindly retried first.
 */
-fn add_client_middleware(client: Client) -> ClientWithMiddleware {
-    ClientBuilder::new(client)
-        .with(RetryTransientMiddleware::new_with_policy(
-            ExponentialBackoff::builder().build_with_max_retries(3),
-        ))
-        .with(TracingMiddleware::default())
-        .build()
+fn add_client_middleware(client: Client, options: &ClientOptions) -> ClientWithMiddleware {
+    let policy = ExponentialBackoff::builder().build_with_max_retries(options.max_retries);
+    let builder = ClientBuilder::new(client);
+    let builder = if let Some(max_wait) = options.rate_limit_max_wait {
+        builder
+            .with(RetryTransientMiddleware::new_with_policy_and_strategy(
+                policy,
+                RateLimitAwareStrategy,
+            ))
+            .with(RateLimitWaitMiddleware { max_wait })
+    } else {
+        builder.with(RetryTransientMiddleware::new_with_policy(policy))
+    };
+    builder.with(TracingMiddleware::default()).build()
 }
 
 /**
     Creates a client with:
 
     - HTTPS only
+    - Proxies from the options, or the `HTTPS_PROXY` / `HTTP_PROXY` / `NO_PROXY` env vars
     - Timeouts for connection and response
     - All common compression algorithms enabled
     - User agent set to `<crate_name>/<crate_version> (<repository_url>)`
+    - Retries for transient errors, as given in the options
+    - Waiting for rate limits to reset, as given in the options
 */
-pub fn create_client(mut default_headers: HeaderMap) -> Result<ClientWithMiddleware, Error> {
+pub fn create_client(
+    mut default_headers: HeaderMap,
+    options: &ClientOptions,
+) -> Result<ClientWithMiddleware, Error> {
     let user_agent = format!(
         "{}/{} ({})",
         env!("CARGO_PKG_NAME"),
@@ -42,15 +242,385 @@ pub fn create_client(mut default_headers: HeaderMap) -> Result<ClientWithMiddlew
 
     default_headers.insert(USER_AGENT, user_agent.parse().unwrap());
 
-    let client = Client::builder()
+    // NOTE: Proxy environment variables are used by default, and
+    // only need to be handled explicitly when overriding the proxy
+    let mut builder = Client::builder();
+    if let Some(proxy) = options.proxy.as_deref() {
+        builder = builder.proxy(Proxy::all(proxy)?.no_proxy(NoProxy::from_env()));
+    }
+
+    let client = builder
         .default_headers(default_headers)
-        .https_only(true)
-        .connect_timeout(Duration::from_secs(15))
-        .timeout(Duration::from_secs(60))
+        .https_only(!options.allow_http)
+        .connect_timeout(Duration::from_secs(CONNECT_TIMEOUT_SECS))
+        .timeout(Duration::from_secs(REQUEST_TIMEOUT_SECS))
         .gzip(true)
         .brotli(true)
         .deflate(true)
         .build()?;
 
-    Ok(add_client_middleware(client))
+    Ok(add_client_middleware(client, options))
+}
+
+/**
+    Reads the full body of a response in chunks, calling the given callback
+    with the number of bytes read so far, and the total number of bytes from
+    the `Content-Length` header of the response, if present.
+*/
+pub(super) async fn read_body_with_progress(
+    mut response: Response,
+    progress: &mut (dyn FnMut(u64, Option<u64>) + Send),
+) -> Result<Vec<u8>, Error> {
+    let total = response.content_length();
+    let mut bytes = Vec::with_capacity(usize::try_from(total.unwrap_or(0)).unwrap_or(0));
+    while let Some(chunk) = response.chunk().await? {
+        bytes.extend_from_slice(&chunk);
+        progress(bytes.len() as u64, total);
+    }
+    Ok(bytes)
+}
+
+/**
+    Checks if a response was rejected due to rate limiting, and if
+    so, returns the time at which the rate limit resets, in seconds
+    since the unix epoch.
+
+    Supports the `x-ratelimit-*` headers used by GitHub, the
+    `ratelimit-*` headers used by GitLab and others, as well as
+    the standard `Retry-After` header, given in seconds.
+*/
+pub(super) fn rate_limit_reset(status: StatusCode, headers: &HeaderMap) -> Option<u64> {
+    if !matches!(
+        status,
+        StatusCode::FORBIDDEN | StatusCode::TOO_MANY_REQUESTS
+    ) {
+        return None;
+    }
+
+    // NOTE: GitHub also sends a Retry-After header for secondary rate
+    // limits, which may be hit even with remaining requests in the limit
+    let retry_after = headers
+        .get(RETRY_AFTER)
+        .and_then(|value| value.to_str().ok()?.trim().parse::<u64>().ok());
+    if let Some(retry_after) = retry_after {
+        return Some(unix_now().saturating_add(retry_after));
+    }
+
+    let get_header = |names: [&'static str; 2]| {
+        names.into_iter().find_map(|name| {
+            let value = headers.get(HeaderName::from_static(name))?;
+            value.to_str().ok()?.trim().parse::<u64>().ok()
+        })
+    };
+
+    // A forbidden response may be unrelated to rate limiting,
+    // so for those we also require the remaining count to be zero
+    let remaining = get_header(["x-ratelimit-remaining", "ratelimit-remaining"]);
+    if status == StatusCode::FORBIDDEN && remaining != Some(0) {
+        return None;
+    }
+
+    get_header(["x-ratelimit-reset", "ratelimit-reset"])
+}
+
+/**
+    Checks if a response was rejected due to rate limiting, and if
+    so, returns how long to wait until the rate limit resets.
+
+    Always waits for at least one second, since the reset
+    time may be in the past, or already be reached.
+*/
+fn rate_limit_wait(status: StatusCode, headers: &HeaderMap) -> Option<Duration> {
+    let reset_at = rate_limit_reset(status, headers)?;
+    let wait = reset_at.saturating_sub(unix_now()).max(1);
+    Some(Duration::from_secs(wait))
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_secs())
+}
+
+/**
+    Percent-encodes a string for use as a single segment of a URL path,
+    such as a release tag, which may contain spaces or slashes.
+*/
+pub(super) fn encode_path_segment(segment: &str) -> String {
+    utf8_percent_encode(segment, PATH_SEGMENT).to_string()
+}
+
+/**
+    Checks if a response has a non-success status code, and if so,
+    returns the status code along with the URL that was requested.
+*/
+pub(super) fn failed_status(response: &Response) -> Option<(u16, String)> {
+    let status = response.status();
+    if status.is_success() {
+        None
+    } else {
+        Some((status.as_u16(), response.url().to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use reqwest::header::HeaderValue;
+    use wiremock::{
+        matchers::{method, path},
+        Mock, MockServer, ResponseTemplate,
+    };
+
+    use super::*;
+
+    #[test]
+    fn path_segments_encoded() {
+        assert_eq!(encode_path_segment("v1.2.3"), "v1.2.3");
+        assert_eq!(encode_path_segment("release 1"), "release%201");
+        assert_eq!(encode_path_segment("a+b"), "a+b");
+        assert_eq!(encode_path_segment("release/2024"), "release%2F2024");
+        assert_eq!(encode_path_segment("100%?#"), "100%25%3F%23");
+    }
+
+    fn headers(pairs: &[(&'static str, &'static str)]) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        for (name, value) in pairs {
+            headers.insert(
+                HeaderName::from_static(name),
+                HeaderValue::from_static(value),
+            );
+        }
+        headers
+    }
+
+    #[test]
+    fn rate_limit_reset_github() {
+        let limited = headers(&[
+            ("x-ratelimit-remaining", "0"),
+            ("x-ratelimit-reset", "1700000000"),
+        ]);
+        assert_eq!(
+            rate_limit_reset(StatusCode::FORBIDDEN, &limited),
+            Some(1_700_000_000)
+        );
+        assert_eq!(
+            rate_limit_reset(StatusCode::TOO_MANY_REQUESTS, &limited),
+            Some(1_700_000_000)
+        );
+    }
+
+    #[test]
+    fn rate_limit_reset_gitlab() {
+        let limited = headers(&[
+            ("ratelimit-remaining", "0"),
+            ("ratelimit-reset", "1700000000"),
+        ]);
+        assert_eq!(
+            rate_limit_reset(StatusCode::TOO_MANY_REQUESTS, &limited),
+            Some(1_700_000_000)
+        );
+    }
+
+    #[test]
+    fn rate_limit_reset_not_limited() {
+        // Successful responses are never rate limited
+        let limited = headers(&[
+            ("x-ratelimit-remaining", "0"),
+            ("x-ratelimit-reset", "1700000000"),
+        ]);
+        assert_eq!(rate_limit_reset(StatusCode::OK, &limited), None);
+        // Forbidden responses with remaining requests are not rate limited
+        let remaining = headers(&[
+            ("x-ratelimit-remaining", "42"),
+            ("x-ratelimit-reset", "1700000000"),
+        ]);
+        assert_eq!(rate_limit_reset(StatusCode::FORBIDDEN, &remaining), None);
+        assert_eq!(
+            rate_limit_reset(StatusCode::FORBIDDEN, &HeaderMap::new()),
+            None
+        );
+    }
+
+    #[test]
+    fn rate_limit_reset_retry_after() {
+        let limited = headers(&[("retry-after", "30")]);
+        let reset_at = rate_limit_reset(StatusCode::TOO_MANY_REQUESTS, &limited).unwrap();
+        assert!(reset_at.abs_diff(unix_now() + 30) <= 1);
+        // Secondary rate limits may have requests remaining
+        let secondary = headers(&[("retry-after", "30"), ("x-ratelimit-remaining", "42")]);
+        assert!(rate_limit_reset(StatusCode::FORBIDDEN, &secondary).is_some());
+        assert_eq!(rate_limit_reset(StatusCode::OK, &limited), None);
+    }
+
+    #[test]
+    fn base_url_fallback() {
+        let mut options = ClientOptions::default();
+        assert_eq!(
+            options.base_url_or(ArtifactProvider::GitHub, "https://api.github.com"),
+            "https://api.github.com"
+        );
+        options.base_urls.insert(
+            ArtifactProvider::GitHub,
+            "https://github.mycorp.com/api/v3/".to_string(),
+        );
+        assert_eq!(
+            options.base_url_or(ArtifactProvider::GitHub, "https://api.github.com"),
+            "https://github.mycorp.com/api/v3"
+        );
+        assert_eq!(
+            options.base_url_or(ArtifactProvider::GitLab, "https://gitlab.com/api/v4"),
+            "https://gitlab.com/api/v4"
+        );
+    }
+
+    #[tokio::test]
+    async fn retries_transient_errors() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/asset"))
+            .respond_with(ResponseTemplate::new(503))
+            .up_to_n_times(2)
+            .expect(2)
+            .with_priority(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/asset"))
+            .respond_with(ResponseTemplate::new(200).set_body_bytes(b"contents"))
+            .expect(1)
+            .with_priority(2)
+            .mount(&server)
+            .await;
+
+        let options = test_options(ArtifactProvider::GitHub, server.uri());
+        let client = create_client(HeaderMap::new(), &options).unwrap();
+        let response = client
+            .get(format!("{}/asset", server.uri()))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.bytes().await.unwrap().as_ref(), b"contents");
+    }
+
+    #[tokio::test]
+    async fn refuses_plain_http_by_default() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/asset"))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(0)
+            .mount(&server)
+            .await;
+
+        let client = create_client(HeaderMap::new(), &ClientOptions::default()).unwrap();
+        let result = client.get(format!("{}/asset", server.uri())).send().await;
+        assert!(result.is_err());
+    }
+
+    #[tokio::test]
+    async fn does_not_retry_not_found() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/asset"))
+            .respond_with(ResponseTemplate::new(404))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let options = test_options(ArtifactProvider::GitHub, server.uri());
+        let client = create_client(HeaderMap::new(), &options).unwrap();
+        let response = client
+            .get(format!("{}/asset", server.uri()))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn gives_up_after_max_retries() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/asset"))
+            .respond_with(ResponseTemplate::new(500))
+            .expect(2)
+            .mount(&server)
+            .await;
+
+        let options = ClientOptions {
+            max_retries: 1,
+            ..test_options(ArtifactProvider::GitHub, server.uri())
+        };
+        let client = create_client(HeaderMap::new(), &options).unwrap();
+        let response = client
+            .get(format!("{}/asset", server.uri()))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::INTERNAL_SERVER_ERROR);
+    }
+
+    #[tokio::test]
+    async fn waits_for_rate_limit_reset() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/asset"))
+            .respond_with(ResponseTemplate::new(429).insert_header("retry-after", "1"))
+            .up_to_n_times(1)
+            .expect(1)
+            .with_priority(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/asset"))
+            .respond_with(ResponseTemplate::new(200).set_body_bytes(b"contents"))
+            .expect(1)
+            .with_priority(2)
+            .mount(&server)
+            .await;
+
+        // NOTE: Retries are left at their default, and must not
+        // make any additional requests for rate limited responses
+        let options = ClientOptions {
+            rate_limit_max_wait: Some(Duration::from_secs(10)),
+            ..test_options(ArtifactProvider::GitHub, server.uri())
+        };
+        assert_eq!(options.max_retries, DEFAULT_MAX_RETRIES);
+        let client = create_client(HeaderMap::new(), &options).unwrap();
+        let response = client
+            .get(format!("{}/asset", server.uri()))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.bytes().await.unwrap().as_ref(), b"contents");
+    }
+
+    #[tokio::test]
+    async fn does_not_wait_past_bound() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/asset"))
+            .respond_with(ResponseTemplate::new(429).insert_header("retry-after", "3600"))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        // NOTE: Retries are left at their default, and must not
+        // make any additional requests for rate limited responses
+        let options = ClientOptions {
+            rate_limit_max_wait: Some(Duration::from_secs(10)),
+            ..test_options(ArtifactProvider::GitHub, server.uri())
+        };
+        assert_eq!(options.max_retries, DEFAULT_MAX_RETRIES);
+        let client = create_client(HeaderMap::new(), &options).unwrap();
+        let response = client
+            .get(format!("{}/asset", server.uri()))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+        assert!(rate_limit_reset(response.status(), response.headers()).is_some());
+    }
 }
@@ -1,4 +1,4 @@
-use std::io::Read;
+use std::io::{self, BufReader, Read};
 
 use flate2::read::GzDecoder;
 use tokio::{task::spawn_blocking, time::Instant};
@@ -26,3 +26,25 @@ pub async fn decompress_gzip(gz_contents: impl AsRef<[u8]>) -> RokitResult<Vec<u
     })
     .await?
 }
+
+pub async fn decompress_xz(xz_contents: impl AsRef<[u8]>) -> RokitResult<Vec<u8>> {
+    let xz_contents = xz_contents.as_ref().to_vec();
+    let num_kilobytes = xz_contents.len() / 1024;
+    let start = Instant::now();
+
+    // Same as for gzip, decompressing xz is a potentially expensive operation
+    spawn_blocking(move || {
+        let mut reader = BufReader::new(xz_contents.as_slice());
+        let mut contents = Vec::new();
+        lzma_rs::xz_decompress(&mut reader, &mut contents)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+
+        tracing::trace!(
+            num_kilobytes,
+            elapsed = ?start.elapsed(),
+            "decompressed xz"
+        );
+        Ok(contents)
+    })
+    .await?
+}
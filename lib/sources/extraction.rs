@@ -1,17 +1,24 @@
 #![allow(clippy::struct_excessive_bools)]
 
 use std::{
+    collections::BTreeMap,
     env::consts::{EXE_EXTENSION, EXE_SUFFIX},
     io::{self, Read},
     path::{Path, PathBuf, MAIN_SEPARATOR_STR},
 };
 
+use sevenz_rust::{Password, SevenZArchiveEntry, SevenZReader};
 use tar::Archive as TarArchive;
 use thiserror::Error;
 use tokio::{task::spawn_blocking, time::Instant};
 use zip::ZipArchive;
 
-use crate::{descriptor::OS, result::RokitResult, sources::ArtifactFormat};
+use crate::{
+    descriptor::OS,
+    result::{RokitError, RokitResult},
+    sources::ArtifactFormat,
+    util::str::glob_matches,
+};
 
 #[derive(Debug, Error)]
 pub enum ExtractError {
@@ -26,6 +33,16 @@ pub enum ExtractError {
         file_name: String,
         archive_name: String,
     },
+    #[error(
+        "missing binaries '{}' \
+        in {format} file '{archive_name}'",
+        file_names.join("', '")
+    )]
+    FilesMissing {
+        format: ArtifactFormat,
+        file_names: Vec<String>,
+        archive_name: String,
+    },
     #[error(
         "mismatch in OS for binary '{file_name}' in archive '{archive_name}'\
         \ncurrent OS is {current_os:?}, binary is {file_os:?}"
@@ -47,6 +64,37 @@ pub enum ExtractError {
     },
 }
 
+// NOTE: Symlinks may point to other symlinks, but never infinitely
+const MAX_LINK_DEPTH: usize = 8;
+
+// NOTE: 7z archives created on unix systems store the unix file mode
+// in the upper 16 bits of their attributes, when this flag is set
+const SEVEN_Z_UNIX_EXTENSION: u32 = 0x8000;
+const UNIX_FILE_TYPE_MASK: u32 = 0o170_000;
+const UNIX_SYMLINK_TYPE: u32 = 0o120_000;
+
+/**
+    A file extracted from an archive, which is either a regular file
+    with its contents, or a symlink to another file in the same archive.
+
+    Symlink targets are relative to the directory containing the symlink.
+*/
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ExtractedFile {
+    Contents(Vec<u8>),
+    Symlink(PathBuf),
+}
+
+/**
+    A binary extracted from an archive, along with the unix file mode
+    of its entry in the archive, if the archive stored a valid one.
+*/
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExtractedBinary {
+    pub contents: Vec<u8>,
+    pub mode: Option<u32>,
+}
+
 /**
     A candidate for extraction from an archive.
 
@@ -59,15 +107,24 @@ struct Candidate {
     matched_full_path: bool,
     matched_file_exact: bool,   // Case-sensitive filename match
     matched_file_inexact: bool, // Case-insensitive filename match
+    matched_file_stem: bool,    // Case-insensitive match without `.exe` (Windows only)
     has_exec_perms: bool,       // Has executable permissions (UNIX only)
     has_exec_suffix: bool,      // Has an executable suffix (e.g. `.exe`)
 }
 
 impl Candidate {
+    fn matched_name(&self) -> bool {
+        self.matched_full_path
+            || self.matched_file_exact
+            || self.matched_file_inexact
+            || self.matched_file_stem
+    }
+
     fn priority(&self) -> u32 {
         u32::from(self.matched_full_path)
             + u32::from(self.matched_file_exact)
             + u32::from(self.matched_file_inexact)
+            + u32::from(self.matched_file_stem)
             + u32::from(self.has_exec_perms)
             + u32::from(self.has_exec_suffix)
     }
@@ -79,6 +136,7 @@ impl Candidate {
         let entry_paths = entry_paths.as_ref();
         let desired_file_path = desired_file_path.as_ref();
         let desired_file_name = desired_file_path.file_name()?.to_str()?;
+        let desired_file_stem = strip_exe_suffix(desired_file_name);
 
         // Gather all candidates
         let mut candidates = entry_paths
@@ -94,15 +152,25 @@ impl Candidate {
                 let matched_file_exact = file_name == Some(desired_file_name);
                 let matched_file_inexact =
                     file_name.is_some_and(|name| name.eq_ignore_ascii_case(desired_file_name));
+                // NOTE: On Windows, archives may contain binaries without the `.exe`
+                // extension, which we still want to find, but with a lower priority
+                let matched_file_stem = !EXE_SUFFIX.is_empty()
+                    && file_name.is_some_and(|name| {
+                        strip_exe_suffix(name).eq_ignore_ascii_case(desired_file_stem)
+                    });
 
-                let has_exec_perms = perms.map_or(false, |perms| (perms & 0o111) != 0);
-                let has_exec_suffix = path.extension().map_or(false, |ext| ext == EXE_EXTENSION);
+                let has_exec_perms = perms.is_some_and(|perms| (perms & 0o111) != 0);
+                let has_exec_suffix = !EXE_EXTENSION.is_empty()
+                    && path
+                        .extension()
+                        .is_some_and(|ext| ext.eq_ignore_ascii_case(EXE_EXTENSION));
 
                 Some(Self {
                     path: path.clone(),
                     matched_full_path,
                     matched_file_exact,
                     matched_file_inexact,
+                    matched_file_stem,
                     has_exec_perms,
                     has_exec_suffix,
                 })
@@ -122,17 +190,100 @@ impl Candidate {
 }
 
 /**
-    Searches for and extracts the best matching file from a zip archive.
+    Strips the executable suffix of the current platform from the given
+    file name, if it has one, ignoring casing - so `tool.EXE` becomes
+    `tool` on Windows, and file names are left as-is on other platforms.
+*/
+fn strip_exe_suffix(file_name: &str) -> &str {
+    let Some(idx) = file_name.len().checked_sub(EXE_SUFFIX.len()) else {
+        return file_name;
+    };
+    if EXE_SUFFIX.is_empty() || !file_name.is_char_boundary(idx) {
+        return file_name;
+    }
+    if file_name[idx..].eq_ignore_ascii_case(EXE_SUFFIX) {
+        &file_name[..idx]
+    } else {
+        file_name
+    }
+}
+
+/**
+    Sanitizes the path of an archive entry, making sure that it is relative
+    and does not traverse upwards, outside of the directory it is extracted to.
 
-    May return `None` if no desired file was found in the archive.
+    Returns the normalized path, without any `.` or `..` components.
 */
-pub async fn extract_zip_file(
-    zip_contents: impl AsRef<[u8]>,
-    desired_file_name: impl Into<String>,
-) -> RokitResult<Option<Vec<u8>>> {
-    let desired_file_name = format!("{}{EXE_SUFFIX}", desired_file_name.into());
-    let desired_file_path = PathBuf::from(&desired_file_name);
+fn sanitize_entry_path(entry_name: &str) -> RokitResult<PathBuf> {
+    if is_absolute_entry_path(entry_name) {
+        return Err(RokitError::UnsafeArchivePath(entry_name.to_string()));
+    }
+
+    let mut components = Vec::new();
+    for component in entry_name.split(['/', '\\']) {
+        match component {
+            "" | "." => {}
+            ".." => {
+                if components.pop().is_none() {
+                    return Err(RokitError::UnsafeArchivePath(entry_name.to_string()));
+                }
+            }
+            _ => components.push(component),
+        }
+    }
 
+    Ok(components.into_iter().collect())
+}
+
+/*
+    Checks if the given entry path is absolute, meaning that it starts
+    with a separator, or with a Windows drive prefix such as `C:`.
+
+    Colons elsewhere in a path are valid in entries from Unix archives.
+*/
+fn is_absolute_entry_path(entry_name: &str) -> bool {
+    let bytes = entry_name.as_bytes();
+    let has_drive_prefix = bytes.len() >= 2 && bytes[0].is_ascii_alphabetic() && bytes[1] == b':';
+    entry_name.starts_with(['/', '\\']) || has_drive_prefix
+}
+
+/**
+    Resolves the target of a symlink entry in an archive, relative to the
+    archive root, making sure that it does not point outside of the archive.
+
+    Returns the normalized path of the target, without any `.` or `..` components.
+*/
+fn resolve_link_target(link_path: &Path, target: &str) -> RokitResult<PathBuf> {
+    if is_absolute_entry_path(target) {
+        return Err(RokitError::UnsafeArchivePath(target.to_string()));
+    }
+
+    let mut joined = link_path
+        .parent()
+        .into_iter()
+        .flat_map(Path::iter)
+        .map(|component| component.to_string_lossy().to_string())
+        .collect::<Vec<_>>();
+    joined.push(target.to_string());
+
+    sanitize_entry_path(&joined.join("/"))
+        .map_err(|_| RokitError::UnsafeArchivePath(target.to_string()))
+}
+
+/**
+    Searches for and extracts the best matching files from a zip archive.
+
+    Returns a map of desired file names to their binaries, which will
+    not contain any desired files that were not found in the archive.
+
+    The given callback is called with the number of candidate entries
+    read so far, and the total number of candidate entries to read.
+*/
+pub async fn extract_zip_files(
+    zip_contents: impl AsRef<[u8]>,
+    desired_file_names: Vec<String>,
+    mut progress: impl FnMut(u64, Option<u64>) + Send + 'static,
+) -> RokitResult<BTreeMap<String, ExtractedBinary>> {
     let zip_contents = zip_contents.as_ref().to_vec();
     let num_kilobytes = zip_contents.len() / 1024;
     let start = Instant::now();
@@ -140,43 +291,65 @@ pub async fn extract_zip_file(
     // Reading a zip file is a potentially expensive operation, so
     // spawn it as a blocking task and use the tokio thread pool.
     spawn_blocking(move || {
-        let mut found = None;
+        let mut found = BTreeMap::new();
+        let mut processed = 0;
         let mut reader = io::Cursor::new(&zip_contents);
         let mut zip = ZipArchive::new(&mut reader)?;
 
         // Gather paths and their permissions,
         // avoiding reading the entire zip file
-        let entry_paths = zip
-            .file_names()
-            .map(|name| {
-                // NOTE: We don't need to sanitize the files names here
-                // since we only use them for matching *within the zip file*
-                (PathBuf::from(name), None::<u32>)
-            })
-            .collect::<Vec<_>>();
+        let mut sanitized_names = BTreeMap::new();
+        let mut entry_paths = Vec::new();
+        for index in 0..zip.len() {
+            // NOTE: Raw entries are not decompressed, and reading them
+            // never fails for entries with unsupported compression methods
+            let entry = zip.by_index_raw(index)?;
+            let name = entry.name();
+            // NOTE: Paths are only used for matching *within the zip file*,
+            // but we still refuse any archives containing unsafe entries.
+            sanitized_names.insert(sanitize_entry_path(name)?, name.to_string());
+            entry_paths.push((PathBuf::from(name), entry.unix_mode()));
+        }
 
-        // Find the best candidate to extract, if any
-        let best = Candidate::find_best(entry_paths, &desired_file_path);
-        if let Some(candidate) = best {
-            if let Some(path_str) = candidate.path.to_str() {
-                if let Ok(mut entry) = zip.by_name(path_str) {
-                    let mut bytes = Vec::new();
-                    entry.read_to_end(&mut bytes)?;
-                    found = Some(bytes);
+        // Find the best candidate to extract for each file, if any,
+        // following any symlinks to the file that they are pointing to
+        let candidates = find_best_candidates(&entry_paths, desired_file_names);
+        let num_candidates = candidates.len() as u64;
+        'candidates: for (file_name, candidate) in candidates {
+            processed += 1;
+            progress(processed, Some(num_candidates));
+            let mut name = candidate.path.to_str().map(ToString::to_string);
+            for _ in 0..MAX_LINK_DEPTH {
+                let Some(mut entry) = name.and_then(|name| zip.by_name(&name).ok()) else {
+                    break;
+                };
+                let mut bytes = Vec::new();
+                entry.read_to_end(&mut bytes)?;
+                if !entry.is_symlink() {
+                    let mode = entry.unix_mode();
+                    found.insert(
+                        file_name,
+                        ExtractedBinary {
+                            contents: bytes,
+                            mode,
+                        },
+                    );
+                    continue 'candidates;
                 }
+                let link_path = sanitize_entry_path(entry.name())?;
+                let target = resolve_link_target(&link_path, &String::from_utf8_lossy(&bytes))?;
+                name = sanitized_names.get(&target).cloned();
             }
-            if found.is_none() {
-                tracing::warn!(
-                    path = ?candidate.path,
-                    "found candidate path, but failed to extract file"
-                );
-            }
+            tracing::warn!(
+                path = ?candidate.path,
+                "found candidate path, but failed to extract file"
+            );
         }
 
         tracing::debug!(
             num_kilobytes,
             elapsed = ?start.elapsed(),
-            found = found.is_some(),
+            found = found.len(),
             "extracted zip file"
         );
         Ok(found)
@@ -185,17 +358,19 @@ pub async fn extract_zip_file(
 }
 
 /**
-    Searches for and extracts the best matching file from a tar archive.
+    Searches for and extracts the best matching files from a tar archive.
 
-    May return `None` if no desired file was found in the archive.
+    Returns a map of desired file names to their binaries, which will
+    not contain any desired files that were not found in the archive.
+
+    The given callback is called with the number of entries processed so
+    far - tar archives have no index, so the total number is never known.
 */
-pub async fn extract_tar_file(
+pub async fn extract_tar_files(
     tar_contents: impl AsRef<[u8]>,
-    desired_file_name: impl Into<String>,
-) -> RokitResult<Option<Vec<u8>>> {
-    let desired_file_name = format!("{}{EXE_SUFFIX}", desired_file_name.into());
-    let desired_file_path = PathBuf::from(&desired_file_name);
-
+    desired_file_names: Vec<String>,
+    mut progress: impl FnMut(u64, Option<u64>) + Send + 'static,
+) -> RokitResult<BTreeMap<String, ExtractedBinary>> {
     let tar_contents = tar_contents.as_ref().to_vec();
     let num_kilobytes = tar_contents.len() / 1024;
     let start = Instant::now();
@@ -203,7 +378,7 @@ pub async fn extract_tar_file(
     // Reading a tar file is a potentially expensive operation, so
     // spawn it as a blocking task and use the tokio thread pool.
     spawn_blocking(move || {
-        let mut found = None;
+        let mut found = BTreeMap::new();
 
         /*
             Gather paths and their permissions - note that we
@@ -215,49 +390,908 @@ pub async fn extract_tar_file(
         */
         let mut entry_cursor = io::Cursor::new(&tar_contents);
         let mut entry_reader = TarArchive::new(&mut entry_cursor);
-        let entry_paths = entry_reader
-            .entries_with_seek()?
-            .filter_map(|entry| {
-                let entry = entry.ok()?;
-                if entry.header().entry_type().is_dir() {
-                    return None;
+        let mut entry_paths = Vec::new();
+        let mut link_targets = BTreeMap::new();
+        for (entry, processed) in entry_reader.entries_with_seek()?.zip(1..) {
+            progress(processed, None);
+            let Ok(entry) = entry else {
+                continue;
+            };
+            let sanitized = sanitize_entry_path(&String::from_utf8_lossy(&entry.path_bytes()))?;
+            if entry.header().entry_type().is_dir() {
+                continue;
+            }
+            if entry.header().entry_type().is_symlink() {
+                let target = entry.link_name_bytes().unwrap_or_default();
+                let target = resolve_link_target(&sanitized, &String::from_utf8_lossy(&target))?;
+                link_targets.insert(sanitized, target);
+            }
+            let Ok(path) = entry.path() else {
+                continue;
+            };
+            let perms = entry.header().mode().ok();
+            entry_paths.push((path.to_path_buf(), perms));
+        }
+
+        // Find the best candidate to extract for each file, if any,
+        // following any symlinks to the file that they are pointing to
+        let mut candidates = find_best_candidates(&entry_paths, desired_file_names)
+            .into_iter()
+            .map(|(file_name, candidate)| {
+                let path = candidate.path.to_string_lossy();
+                let mut path = sanitize_entry_path(&path)?;
+                for _ in 0..MAX_LINK_DEPTH {
+                    match link_targets.get(&path) {
+                        Some(target) => path = target.clone(),
+                        None => break,
+                    }
                 }
-                let path = entry.path().ok()?;
-                let perms = entry.header().mode().ok();
-                Some((path.to_path_buf(), perms))
+                Ok((file_name, path))
             })
-            .collect::<Vec<_>>();
-
-        // Find the best candidate to extract, if any
-        let best = Candidate::find_best(entry_paths, &desired_file_path);
-        if let Some(candidate) = best {
+            .collect::<RokitResult<BTreeMap<_, _>>>()?;
+        if !candidates.is_empty() {
             let contents_cursor = io::Cursor::new(&tar_contents);
             let mut contents_reader = TarArchive::new(contents_cursor);
             for entry in contents_reader.entries_with_seek()? {
                 let mut entry = entry?;
-                let entry_path = entry.path()?;
-                if entry_path == candidate.path.as_path() {
-                    let mut bytes = Vec::new();
-                    entry.read_to_end(&mut bytes)?;
-                    found = Some(bytes);
+                if entry.header().entry_type().is_symlink() {
+                    continue;
+                }
+                let entry_path =
+                    sanitize_entry_path(&String::from_utf8_lossy(&entry.path_bytes()))?;
+                let matching = candidates
+                    .iter()
+                    .filter(|(_, path)| **path == entry_path)
+                    .map(|(file_name, _)| file_name.clone())
+                    .collect::<Vec<_>>();
+                if matching.is_empty() {
+                    continue;
+                }
+                let mut bytes = Vec::new();
+                entry.read_to_end(&mut bytes)?;
+                let mode = entry.header().mode().ok();
+                for file_name in matching {
+                    candidates.remove(&file_name);
+                    let contents = bytes.clone();
+                    found.insert(file_name, ExtractedBinary { contents, mode });
+                }
+                if candidates.is_empty() {
                     break;
                 }
             }
-            if found.is_none() {
-                tracing::warn!(
-                    path = ?candidate.path,
-                    "found candidate path, but failed to extract file"
-                );
+            for path in candidates.values() {
+                tracing::warn!(?path, "found candidate path, but failed to extract file");
             }
         }
 
         tracing::debug!(
             num_kilobytes,
             elapsed = ?start.elapsed(),
-            found = found.is_some(),
+            found = found.len(),
             "extracted tar file"
         );
         Ok(found)
     })
     .await?
 }
+
+/**
+    Searches for and extracts the best matching files from a 7z archive.
+
+    Returns a map of desired file names to their binaries, which will
+    not contain any desired files that were not found in the archive.
+
+    Entries are decompressed one at a time, and only the contents of the
+    desired files are kept in memory. Symlinks in 7z archives are not
+    supported, and are never extracted.
+*/
+pub async fn extract_7z_files(
+    contents: impl AsRef<[u8]>,
+    desired_file_names: Vec<String>,
+    mut progress: impl FnMut(u64, Option<u64>) + Send + 'static,
+) -> RokitResult<BTreeMap<String, ExtractedBinary>> {
+    let contents = contents.as_ref().to_vec();
+    let num_kilobytes = contents.len() / 1024;
+    let start = Instant::now();
+
+    // Same as for zip and tar files, reading a 7z file is potentially expensive
+    spawn_blocking(move || {
+        let mut found = BTreeMap::new();
+        let cursor = io::Cursor::new(&contents);
+        let mut reader = SevenZReader::new(cursor, contents.len() as u64, Password::empty())?;
+
+        // Gather paths and their permissions from the archive
+        // header, without decompressing any of the file contents
+        let mut sanitized_names = BTreeMap::new();
+        let mut entry_paths = Vec::new();
+        let num_entries = reader.archive().files.len() as u64;
+        for entry in &reader.archive().files {
+            let sanitized = sanitize_entry_path(entry.name())?;
+            if entry.is_directory() || is_7z_symlink(entry) {
+                continue;
+            }
+            sanitized_names.insert(entry.name().to_string(), sanitized.clone());
+            entry_paths.push((sanitized, unix_mode_7z(entry)));
+        }
+
+        let candidates = find_best_candidates(&entry_paths, desired_file_names)
+            .into_iter()
+            .map(|(file_name, candidate)| (file_name, candidate.path))
+            .collect::<BTreeMap<_, _>>();
+        if !candidates.is_empty() {
+            let mut processed = 0;
+            reader.for_each_entries(|entry, entry_reader| {
+                if found.len() == candidates.len() {
+                    return Ok(false);
+                }
+                processed += 1;
+                progress(processed, Some(num_entries));
+
+                let entry_path = sanitized_names.get(entry.name());
+                let matching = candidates
+                    .iter()
+                    .filter(|(_, path)| Some(*path) == entry_path)
+                    .map(|(file_name, _)| file_name.clone())
+                    .collect::<Vec<_>>();
+                if matching.is_empty() {
+                    // NOTE: Entries in solid archives must be decompressed in order,
+                    // so any entries before the ones we want must still be read
+                    io::copy(entry_reader, &mut io::sink())?;
+                } else {
+                    let mut bytes = Vec::new();
+                    entry_reader.read_to_end(&mut bytes)?;
+                    let mode = unix_mode_7z(entry);
+                    for file_name in matching {
+                        let contents = bytes.clone();
+                        found.insert(file_name, ExtractedBinary { contents, mode });
+                    }
+                }
+                Ok(true)
+            })?;
+        }
+
+        tracing::debug!(
+            num_kilobytes,
+            elapsed = ?start.elapsed(),
+            found = found.len(),
+            "extracted 7z file"
+        );
+        Ok(found)
+    })
+    .await?
+}
+
+/**
+    Extracts all files matching any of the given glob-style patterns, such as
+    `completions/tool.*` or `*.so`, from a zip archive, along with their
+    sanitized relative paths.
+
+    Patterns are matched against the full `/`-separated path of each entry,
+    and any symlinks must point to files within the archive.
+*/
+pub async fn extract_zip_matching(
+    zip_contents: impl AsRef<[u8]>,
+    patterns: Vec<String>,
+) -> RokitResult<Vec<(PathBuf, ExtractedFile)>> {
+    let zip_contents = zip_contents.as_ref().to_vec();
+
+    // Same as for single files, reading a zip file is potentially expensive
+    spawn_blocking(move || {
+        let mut found = Vec::new();
+        let mut reader = io::Cursor::new(&zip_contents);
+        let mut zip = ZipArchive::new(&mut reader)?;
+
+        for index in 0..zip.len() {
+            let mut entry = zip.by_index(index)?;
+            let path = sanitize_entry_path(entry.name())?;
+            if entry.is_dir() || !path_matches_any(&path, &patterns) {
+                continue;
+            }
+            let mut bytes = Vec::new();
+            entry.read_to_end(&mut bytes)?;
+            if entry.is_symlink() {
+                let target = String::from_utf8_lossy(&bytes).to_string();
+                resolve_link_target(&path, &target)?;
+                found.push((path, ExtractedFile::Symlink(PathBuf::from(target))));
+            } else {
+                found.push((path, ExtractedFile::Contents(bytes)));
+            }
+        }
+
+        tracing::debug!(found = found.len(), "extracted matching zip files");
+        Ok(found)
+    })
+    .await?
+}
+
+/**
+    Extracts all files matching any of the given glob-style patterns, such as
+    `completions/tool.*` or `*.so`, from a tar archive, along with their
+    sanitized relative paths.
+
+    Patterns are matched against the full `/`-separated path of each entry,
+    and any symlinks must point to files within the archive.
+*/
+pub async fn extract_tar_matching(
+    tar_contents: impl AsRef<[u8]>,
+    patterns: Vec<String>,
+) -> RokitResult<Vec<(PathBuf, ExtractedFile)>> {
+    let tar_contents = tar_contents.as_ref().to_vec();
+
+    // Same as for single files, reading a tar file is potentially expensive
+    spawn_blocking(move || {
+        let mut found = Vec::new();
+        let cursor = io::Cursor::new(&tar_contents);
+        let mut reader = TarArchive::new(cursor);
+
+        for entry in reader.entries_with_seek()? {
+            let mut entry = entry?;
+            let path = sanitize_entry_path(&String::from_utf8_lossy(&entry.path_bytes()))?;
+            let entry_type = entry.header().entry_type();
+            if !(entry_type.is_file() || entry_type.is_symlink())
+                || !path_matches_any(&path, &patterns)
+            {
+                continue;
+            }
+            if entry_type.is_symlink() {
+                let target = entry.link_name_bytes().unwrap_or_default();
+                let target = String::from_utf8_lossy(&target).to_string();
+                resolve_link_target(&path, &target)?;
+                found.push((path, ExtractedFile::Symlink(PathBuf::from(target))));
+            } else {
+                let mut bytes = Vec::new();
+                entry.read_to_end(&mut bytes)?;
+                found.push((path, ExtractedFile::Contents(bytes)));
+            }
+        }
+
+        tracing::debug!(found = found.len(), "extracted matching tar files");
+        Ok(found)
+    })
+    .await?
+}
+
+/**
+    Extracts all files matching any of the given glob-style patterns, such as
+    `completions/tool.*` or `*.so`, from a 7z archive, along with their
+    sanitized relative paths.
+
+    Patterns are matched against the full `/`-separated path of each entry.
+    Symlinks in 7z archives are not supported, and are never extracted.
+*/
+pub async fn extract_7z_matching(
+    contents: impl AsRef<[u8]>,
+    patterns: Vec<String>,
+) -> RokitResult<Vec<(PathBuf, ExtractedFile)>> {
+    let contents = contents.as_ref().to_vec();
+
+    // Same as for single files, reading a 7z file is potentially expensive
+    spawn_blocking(move || {
+        let mut found = Vec::new();
+        let cursor = io::Cursor::new(&contents);
+        let mut reader = SevenZReader::new(cursor, contents.len() as u64, Password::empty())?;
+
+        let mut matching_names = BTreeMap::new();
+        for entry in &reader.archive().files {
+            let path = sanitize_entry_path(entry.name())?;
+            if entry.is_directory() || is_7z_symlink(entry) || !path_matches_any(&path, &patterns) {
+                continue;
+            }
+            matching_names.insert(entry.name().to_string(), path);
+        }
+
+        if !matching_names.is_empty() {
+            reader.for_each_entries(|entry, entry_reader| {
+                if let Some(path) = matching_names.get(entry.name()) {
+                    let mut bytes = Vec::new();
+                    entry_reader.read_to_end(&mut bytes)?;
+                    found.push((path.clone(), ExtractedFile::Contents(bytes)));
+                } else {
+                    io::copy(entry_reader, &mut io::sink())?;
+                }
+                Ok(found.len() < matching_names.len())
+            })?;
+        }
+
+        tracing::debug!(found = found.len(), "extracted matching 7z files");
+        Ok(found)
+    })
+    .await?
+}
+
+fn unix_mode_7z(entry: &SevenZArchiveEntry) -> Option<u32> {
+    let attributes = entry.windows_attributes();
+    (entry.has_windows_attributes && (attributes & SEVEN_Z_UNIX_EXTENSION) != 0)
+        .then_some(attributes >> 16)
+}
+
+fn is_7z_symlink(entry: &SevenZArchiveEntry) -> bool {
+    unix_mode_7z(entry).is_some_and(|mode| (mode & UNIX_FILE_TYPE_MASK) == UNIX_SYMLINK_TYPE)
+}
+
+fn path_matches_any(path: &Path, patterns: &[String]) -> bool {
+    let path = path
+        .iter()
+        .map(|component| component.to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/");
+    patterns.iter().any(|pattern| glob_matches(pattern, &path))
+}
+
+/**
+    Finds the best candidate for each of the desired file names.
+
+    When looking for a single file, any executable may be a candidate, but when
+    looking for several files, candidates must match on their names, to prevent
+    one desired file from being extracted in place of another missing file.
+*/
+fn find_best_candidates(
+    entry_paths: &[(PathBuf, Option<u32>)],
+    desired_file_names: Vec<String>,
+) -> BTreeMap<String, Candidate> {
+    let require_name_match = desired_file_names.len() > 1;
+    desired_file_names
+        .into_iter()
+        .filter_map(|file_name| {
+            // NOTE: Executables on Windows must have the `.exe` suffix, which
+            // we always look for first, unless it was already given to us
+            let desired_file_path = if strip_exe_suffix(&file_name).len() < file_name.len() {
+                PathBuf::from(&file_name)
+            } else {
+                PathBuf::from(format!("{file_name}{EXE_SUFFIX}"))
+            };
+            let candidate = Candidate::find_best(entry_paths, &desired_file_path)?;
+            if require_name_match && !candidate.matched_name() {
+                return None;
+            }
+            Some((file_name, candidate))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        io::Write,
+        sync::{Arc, Mutex},
+    };
+
+    use sevenz_rust::{SeqReader, SevenZWriter, SourceReader};
+    use zip::{write::SimpleFileOptions, ZipWriter};
+
+    use super::*;
+
+    fn build_zip(files: &[(&str, &[u8])]) -> Vec<u8> {
+        let mut writer = ZipWriter::new(io::Cursor::new(Vec::new()));
+        for (path, contents) in files {
+            writer
+                .start_file(*path, SimpleFileOptions::default())
+                .unwrap();
+            writer.write_all(contents).unwrap();
+        }
+        writer.finish().unwrap().into_inner()
+    }
+
+    async fn extract_zip_file(zip: &[u8], file_name: &str) -> RokitResult<Option<Vec<u8>>> {
+        let mut found = extract_zip_files(zip, vec![file_name.to_string()], |_, _| {}).await?;
+        Ok(found.remove(file_name).map(|binary| binary.contents))
+    }
+
+    async fn extract_tar_file(tar: &[u8], file_name: &str) -> RokitResult<Option<Vec<u8>>> {
+        let mut found = extract_tar_files(tar, vec![file_name.to_string()], |_, _| {}).await?;
+        Ok(found.remove(file_name).map(|binary| binary.contents))
+    }
+
+    fn build_tar(files: &[(&str, u32, &[u8])]) -> Vec<u8> {
+        let mut builder = tar::Builder::new(Vec::new());
+        for (path, mode, contents) in files {
+            let mut header = tar::Header::new_gnu();
+            header.set_size(contents.len() as u64);
+            header.set_mode(*mode);
+            header.set_cksum();
+            builder.append_data(&mut header, path, *contents).unwrap();
+        }
+        builder.into_inner().unwrap()
+    }
+
+    #[tokio::test]
+    async fn extract_tar_single_binary() {
+        let binary_name = format!("tool{EXE_SUFFIX}");
+        let tar = build_tar(&[
+            ("README.md", 0o644, b"# tool"),
+            (binary_name.as_str(), 0o755, b"binary contents"),
+            ("LICENSE.txt", 0o644, b"MIT"),
+        ]);
+        let found = extract_tar_file(&tar, "tool").await.unwrap();
+        assert_eq!(found.as_deref(), Some(&b"binary contents"[..]));
+    }
+
+    #[tokio::test]
+    async fn extract_tar_nested_binary() {
+        let binary_path = format!("tool-v1.0.0/bin/tool{EXE_SUFFIX}");
+        let tar = build_tar(&[
+            ("tool-v1.0.0/README.md", 0o644, b"# tool"),
+            (binary_path.as_str(), 0o755, b"binary contents"),
+        ]);
+        let found = extract_tar_file(&tar, "tool").await.unwrap();
+        assert_eq!(found.as_deref(), Some(&b"binary contents"[..]));
+    }
+
+    #[tokio::test]
+    async fn extract_tar_missing_binary() {
+        let tar = build_tar(&[
+            ("README.md", 0o644, b"# tool"),
+            ("LICENSE.txt", 0o644, b"MIT"),
+        ]);
+        let found = extract_tar_file(&tar, "tool").await.unwrap();
+        assert_eq!(found, None);
+    }
+
+    #[test]
+    fn sanitize_paths_valid() {
+        let sanitize = |s: &str| sanitize_entry_path(s).unwrap();
+        assert_eq!(sanitize("tool"), PathBuf::from("tool"));
+        assert_eq!(sanitize("./tool"), PathBuf::from("tool"));
+        assert_eq!(sanitize("bin/tool"), PathBuf::from("bin").join("tool"));
+        assert_eq!(sanitize("bin\\tool"), PathBuf::from("bin").join("tool"));
+        assert_eq!(sanitize("bin/../tool"), PathBuf::from("tool"));
+        assert_eq!(sanitize("bin/"), PathBuf::from("bin"));
+    }
+
+    #[test]
+    fn sanitize_paths_with_colons() {
+        // Colons are valid in Unix paths, and only drive prefixes are absolute
+        let sanitize = |s: &str| sanitize_entry_path(s).unwrap();
+        assert_eq!(
+            sanitize("docs:v2/readme"),
+            PathBuf::from("docs:v2").join("readme")
+        );
+        assert_eq!(sanitize("bin/tool:1"), PathBuf::from("bin").join("tool:1"));
+        assert_eq!(sanitize("ab:c"), PathBuf::from("ab:c"));
+        assert!(sanitize_entry_path("c:tool").is_err());
+    }
+
+    #[tokio::test]
+    async fn extract_zip_with_colon_entries() {
+        let zip = build_zip(&[("docs:v2/readme", b"docs"), ("tool", b"binary")]);
+        let found = extract_zip_file(&zip, "tool").await.unwrap();
+        assert_eq!(found.as_deref(), Some(&b"binary"[..]));
+    }
+
+    #[test]
+    fn sanitize_paths_invalid() {
+        for path in [
+            "../tool",
+            "../../etc/cron.d/evil",
+            "bin/../../tool",
+            "..\\tool",
+            "/etc/passwd",
+            "\\Windows\\System32\\evil.exe",
+            "C:\\Windows\\System32\\evil.exe",
+            "C:/evil",
+        ] {
+            assert!(
+                matches!(
+                    sanitize_entry_path(path),
+                    Err(RokitError::UnsafeArchivePath(_))
+                ),
+                "path should be refused: {path}"
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn extract_zip_single_binary() {
+        let binary_name = format!("tool{EXE_SUFFIX}");
+        let zip = build_zip(&[
+            ("README.md", b"# tool"),
+            (binary_name.as_str(), b"binary contents"),
+        ]);
+        let found = extract_zip_file(&zip, "tool").await.unwrap();
+        assert_eq!(found.as_deref(), Some(&b"binary contents"[..]));
+    }
+
+    #[cfg(windows)]
+    #[tokio::test]
+    async fn extract_zip_windows_exe() {
+        let zip = build_zip(&[("README.md", b"# tool"), ("bin/tool.exe", b"exe contents")]);
+        let found = extract_zip_file(&zip, "tool").await.unwrap();
+        assert_eq!(found.as_deref(), Some(&b"exe contents"[..]));
+        // Requesting the binary with its extension should find the same file
+        let found = extract_zip_file(&zip, "tool.exe").await.unwrap();
+        assert_eq!(found.as_deref(), Some(&b"exe contents"[..]));
+    }
+
+    #[cfg(windows)]
+    #[tokio::test]
+    async fn extract_zip_windows_extensionless() {
+        let zip = build_zip(&[("README.md", b"# tool"), ("bin/tool", b"tool contents")]);
+        let found = extract_zip_file(&zip, "tool").await.unwrap();
+        assert_eq!(found.as_deref(), Some(&b"tool contents"[..]));
+    }
+
+    #[cfg(windows)]
+    #[tokio::test]
+    async fn extract_windows_prefers_exe() {
+        let zip = build_zip(&[("tool", b"tool contents"), ("tool.EXE", b"exe contents")]);
+        let found = extract_zip_file(&zip, "tool").await.unwrap();
+        assert_eq!(found.as_deref(), Some(&b"exe contents"[..]));
+
+        let tar = build_tar(&[
+            ("tool", 0o755, b"tool contents"),
+            ("tool.exe", 0o755, b"exe contents"),
+        ]);
+        let found = extract_tar_file(&tar, "tool").await.unwrap();
+        assert_eq!(found.as_deref(), Some(&b"exe contents"[..]));
+    }
+
+    #[tokio::test]
+    async fn extract_zip_multiple_binaries() {
+        let names = ["tool", "tool-helper", "tool-daemon"].map(|n| format!("{n}{EXE_SUFFIX}"));
+        let zip = build_zip(&[
+            ("README.md", b"# tool"),
+            (names[0].as_str(), b"tool contents"),
+            (names[1].as_str(), b"helper contents"),
+            (names[2].as_str(), b"daemon contents"),
+        ]);
+        let found = extract_zip_files(&zip, vec!["tool".into(), "tool-daemon".into()], |_, _| {})
+            .await
+            .unwrap();
+        assert_eq!(found.len(), 2);
+        assert_eq!(found["tool"].contents, b"tool contents");
+        assert_eq!(found["tool-daemon"].contents, b"daemon contents");
+    }
+
+    #[tokio::test]
+    async fn extract_zip_reports_candidate_progress() {
+        let names = ["tool", "tool-helper", "tool-daemon"].map(|n| format!("{n}{EXE_SUFFIX}"));
+        let zip = build_zip(&[
+            ("README.md", b"# tool"),
+            ("LICENSE", b"license"),
+            (names[0].as_str(), b"tool contents"),
+            (names[1].as_str(), b"helper contents"),
+            (names[2].as_str(), b"daemon contents"),
+        ]);
+        let reported = Arc::new(Mutex::new(Vec::new()));
+        let reported_inner = Arc::clone(&reported);
+        extract_zip_files(
+            &zip,
+            vec!["tool".into(), "tool-daemon".into()],
+            move |processed, total| reported_inner.lock().unwrap().push((processed, total)),
+        )
+        .await
+        .unwrap();
+        // Progress is only reported for the entries that are read,
+        // and not for every entry name in the archive
+        assert_eq!(*reported.lock().unwrap(), [(1, Some(2)), (2, Some(2))]);
+    }
+
+    #[tokio::test]
+    async fn extract_zip_multiple_binaries_missing() {
+        // Missing files must not be replaced by other executables
+        let names = ["tool", "tool-helper"].map(|n| format!("{n}{EXE_SUFFIX}"));
+        let zip = build_zip(&[
+            (names[0].as_str(), b"tool contents"),
+            (names[1].as_str(), b"helper contents"),
+        ]);
+        let found = extract_zip_files(&zip, vec!["tool".into(), "tool-daemon".into()], |_, _| {})
+            .await
+            .unwrap();
+        assert_eq!(found.len(), 1);
+        assert!(found.contains_key("tool"));
+    }
+
+    #[tokio::test]
+    async fn extract_tar_multiple_binaries() {
+        let names = ["tool", "tool-helper"].map(|n| format!("bin/{n}{EXE_SUFFIX}"));
+        let tar = build_tar(&[
+            ("README.md", 0o644, b"# tool"),
+            (names[0].as_str(), 0o755, b"tool contents"),
+            (names[1].as_str(), 0o755, b"helper contents"),
+        ]);
+        let found = extract_tar_files(&tar, vec!["tool".into(), "tool-helper".into()], |_, _| {})
+            .await
+            .unwrap();
+        assert_eq!(found["tool"].contents, b"tool contents");
+        assert_eq!(found["tool-helper"].contents, b"helper contents");
+    }
+
+    #[tokio::test]
+    async fn extract_keeps_unix_modes() {
+        let mut writer = ZipWriter::new(io::Cursor::new(Vec::new()));
+        let options = SimpleFileOptions::default().unix_permissions(0o700);
+        writer.start_file("tool", options).unwrap();
+        writer.write_all(b"tool contents").unwrap();
+        let zip = writer.finish().unwrap().into_inner();
+        let found = extract_zip_files(&zip, vec!["tool".into()], |_, _| {})
+            .await
+            .unwrap();
+        assert_eq!(found["tool"].mode.map(|mode| mode & 0o777), Some(0o700));
+
+        let tar = build_tar(&[("tool", 0o750, b"tool contents")]);
+        let found = extract_tar_files(&tar, vec!["tool".into()], |_, _| {})
+            .await
+            .unwrap();
+        assert_eq!(found["tool"].mode.map(|mode| mode & 0o777), Some(0o750));
+    }
+
+    #[tokio::test]
+    async fn extract_zip_matching_files() {
+        let zip = build_zip(&[
+            ("tool", b"binary contents"),
+            ("bin/tool-helper", b"helper contents"),
+            ("lib/libtool.so", b"library contents"),
+            ("README.md", b"# tool"),
+        ]);
+        let found = extract_zip_matching(&zip, vec!["bin/*".into(), "*.so".into()])
+            .await
+            .unwrap();
+        assert_eq!(
+            found,
+            [
+                (
+                    PathBuf::from("bin").join("tool-helper"),
+                    ExtractedFile::Contents(b"helper contents".to_vec())
+                ),
+                (
+                    PathBuf::from("lib").join("libtool.so"),
+                    ExtractedFile::Contents(b"library contents".to_vec())
+                ),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn extract_tar_matching_files() {
+        let tar = build_tar(&[
+            ("tool", 0o755, b"binary contents"),
+            ("./completions/tool.bash", 0o644, b"complete"),
+            ("LICENSE", 0o644, b"MIT"),
+        ]);
+        let found = extract_tar_matching(&tar, vec!["completions/*".into(), "LICENSE".into()])
+            .await
+            .unwrap();
+        assert_eq!(
+            found,
+            [
+                (
+                    PathBuf::from("completions").join("tool.bash"),
+                    ExtractedFile::Contents(b"complete".to_vec())
+                ),
+                (
+                    PathBuf::from("LICENSE"),
+                    ExtractedFile::Contents(b"MIT".to_vec())
+                ),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn extract_matching_refuses_traversal() {
+        let zip = build_zip(&[
+            ("lib/libtool.so", b"library contents"),
+            ("../../evil.so", b"evil contents"),
+        ]);
+        let result = extract_zip_matching(&zip, vec!["*.so".into()]).await;
+        assert!(matches!(result, Err(RokitError::UnsafeArchivePath(_))));
+    }
+
+    fn build_7z(files: &[(&str, &[u8])], solid: bool) -> Vec<u8> {
+        let mut writer = SevenZWriter::new(io::Cursor::new(Vec::new())).unwrap();
+        let entries = files.iter().map(|(path, _)| {
+            // NOTE: The writer only sets this for non-solid entries by itself
+            let mut entry = SevenZArchiveEntry::new();
+            entry.name = (*path).to_string();
+            entry.has_stream = true;
+            entry
+        });
+        if solid {
+            let readers = files
+                .iter()
+                .map(|(_, contents)| SourceReader::from(*contents));
+            writer
+                .push_archive_entries(entries.collect(), SeqReader::new(readers.collect()))
+                .unwrap();
+        } else {
+            for (entry, (_, contents)) in entries.zip(files) {
+                writer.push_archive_entry(entry, Some(*contents)).unwrap();
+            }
+        }
+        writer.finish().unwrap().into_inner()
+    }
+
+    #[tokio::test]
+    async fn extract_7z_single_binary() {
+        let binary_name = format!("tool{EXE_SUFFIX}");
+        let archive = build_7z(
+            &[
+                ("README.md", b"# tool"),
+                (binary_name.as_str(), b"binary contents"),
+            ],
+            false,
+        );
+        assert_eq!(
+            ArtifactFormat::from_contents(&archive),
+            Some(ArtifactFormat::SevenZ)
+        );
+        let mut found = extract_7z_files(&archive, vec!["tool".into()], |_, _| {})
+            .await
+            .unwrap();
+        assert_eq!(
+            found
+                .remove("tool")
+                .map(|binary| binary.contents)
+                .as_deref(),
+            Some(&b"binary contents"[..])
+        );
+    }
+
+    #[tokio::test]
+    async fn extract_7z_solid_multiple_binaries() {
+        let names = ["tool", "tool-helper", "tool-daemon"].map(|n| format!("{n}{EXE_SUFFIX}"));
+        let archive = build_7z(
+            &[
+                ("README.md", b"# tool"),
+                (names[1].as_str(), b"helper contents"),
+                (names[0].as_str(), b"tool contents"),
+                (names[2].as_str(), b"daemon contents"),
+            ],
+            true,
+        );
+        let found = extract_7z_files(
+            &archive,
+            vec!["tool".into(), "tool-daemon".into()],
+            |_, _| {},
+        )
+        .await
+        .unwrap();
+        assert_eq!(found.len(), 2);
+        assert_eq!(found["tool"].contents, b"tool contents");
+        assert_eq!(found["tool-daemon"].contents, b"daemon contents");
+    }
+
+    #[tokio::test]
+    async fn extract_7z_matching_files() {
+        let archive = build_7z(
+            &[
+                ("tool", b"binary contents"),
+                ("lib/libtool.so", b"library contents"),
+                ("README.md", b"# tool"),
+            ],
+            true,
+        );
+        let found = extract_7z_matching(&archive, vec!["*.so".into()])
+            .await
+            .unwrap();
+        assert_eq!(
+            found,
+            [(
+                PathBuf::from("lib").join("libtool.so"),
+                ExtractedFile::Contents(b"library contents".to_vec())
+            )]
+        );
+    }
+
+    #[tokio::test]
+    async fn extract_7z_refuses_traversal() {
+        let archive = build_7z(&[("tool", b"binary contents"), ("../evil", b"evil")], false);
+        let result = extract_7z_files(&archive, vec!["tool".into()], |_, _| {}).await;
+        assert!(matches!(result, Err(RokitError::UnsafeArchivePath(_))));
+    }
+
+    fn build_tar_with_link(files: &[(&str, &[u8])], links: &[(&str, &str)]) -> Vec<u8> {
+        let mut builder = tar::Builder::new(Vec::new());
+        for (path, contents) in files {
+            let mut header = tar::Header::new_gnu();
+            header.set_size(contents.len() as u64);
+            header.set_mode(0o755);
+            header.set_cksum();
+            builder.append_data(&mut header, path, *contents).unwrap();
+        }
+        for (path, target) in links {
+            let mut header = tar::Header::new_gnu();
+            header.set_entry_type(tar::EntryType::Symlink);
+            header.set_mode(0o777);
+            header.set_size(0);
+            builder.append_link(&mut header, path, target).unwrap();
+        }
+        builder.into_inner().unwrap()
+    }
+
+    #[tokio::test]
+    async fn extract_tar_follows_symlinks() {
+        let tar = build_tar_with_link(
+            &[("bin/tool-1.2.3", b"binary contents")],
+            &[("bin/tool", "tool-1.2.3")],
+        );
+        let found = extract_tar_files(&tar, vec!["tool".into()], |_, _| {})
+            .await
+            .unwrap();
+        assert_eq!(found.get("tool").unwrap().contents, b"binary contents");
+        let found = extract_tar_matching(&tar, vec!["bin/*".into()])
+            .await
+            .unwrap();
+        assert_eq!(
+            found,
+            [
+                (
+                    PathBuf::from("bin").join("tool-1.2.3"),
+                    ExtractedFile::Contents(b"binary contents".to_vec())
+                ),
+                (
+                    PathBuf::from("bin").join("tool"),
+                    ExtractedFile::Symlink(PathBuf::from("tool-1.2.3"))
+                ),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn extract_zip_follows_symlinks() {
+        let mut writer = ZipWriter::new(io::Cursor::new(Vec::new()));
+        let options = SimpleFileOptions::default();
+        writer.start_file("bin/tool-1.2.3", options).unwrap();
+        writer.write_all(b"binary contents").unwrap();
+        writer
+            .add_symlink("bin/tool", "tool-1.2.3", options)
+            .unwrap();
+        let zip = writer.finish().unwrap().into_inner();
+        let found = extract_zip_files(&zip, vec!["tool".into(), "other".into()], |_, _| {})
+            .await
+            .unwrap();
+        assert_eq!(found.get("tool").unwrap().contents, b"binary contents");
+        let found = extract_zip_matching(&zip, vec!["bin/tool".into()])
+            .await
+            .unwrap();
+        assert_eq!(
+            found,
+            [(
+                PathBuf::from("bin").join("tool"),
+                ExtractedFile::Symlink(PathBuf::from("tool-1.2.3"))
+            )]
+        );
+    }
+
+    #[tokio::test]
+    async fn extract_refuses_escaping_symlinks() {
+        for target in ["../../etc/passwd", "/etc/passwd", "../../../tool"] {
+            let tar = build_tar_with_link(&[("tool-1.2.3", b"binary")], &[("bin/tool", target)]);
+            let result = extract_tar_files(&tar, vec!["tool".into()], |_, _| {}).await;
+            assert!(matches!(result, Err(RokitError::UnsafeArchivePath(_))));
+            let result = extract_tar_matching(&tar, vec!["*".into()]).await;
+            assert!(matches!(result, Err(RokitError::UnsafeArchivePath(_))));
+        }
+        // Links may point upwards, as long as they stay within the archive
+        let tar = build_tar_with_link(
+            &[("tool-1.2.3", b"binary")],
+            &[("bin/tool", "../tool-1.2.3")],
+        );
+        let found = extract_tar_files(&tar, vec!["tool".into()], |_, _| {})
+            .await
+            .unwrap();
+        assert_eq!(found.get("tool").unwrap().contents, b"binary");
+    }
+
+    #[tokio::test]
+    async fn extract_zip_refuses_traversal() {
+        let binary_name = format!("tool{EXE_SUFFIX}");
+        let evil_name = format!("../../evil/tool{EXE_SUFFIX}");
+        let zip = build_zip(&[
+            (binary_name.as_str(), b"binary contents"),
+            (evil_name.as_str(), b"evil contents"),
+        ]);
+        let result = extract_zip_file(&zip, "tool").await;
+        assert!(matches!(result, Err(RokitError::UnsafeArchivePath(_))));
+    }
+
+    #[tokio::test]
+    async fn extract_zip_refuses_absolute() {
+        let binary_name = format!("tool{EXE_SUFFIX}");
+        let zip = build_zip(&[
+            (binary_name.as_str(), b"binary contents"),
+            ("/etc/cron.d/evil", b"evil contents"),
+        ]);
+        let result = extract_zip_file(&zip, "tool").await;
+        assert!(matches!(result, Err(RokitError::UnsafeArchivePath(_))));
+    }
+}
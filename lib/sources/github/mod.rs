@@ -10,10 +10,20 @@ use reqwest::{
 
 use crate::tool::{ToolId, ToolSpec};
 
-use super::{client::create_client, Artifact, ArtifactProvider, Release};
+use super::{
+    client::{
+        create_client, encode_path_segment, failed_status, rate_limit_reset,
+        read_body_with_progress, ClientOptions,
+    },
+    paging::{fetch_all_pages, versions_from_tags},
+    Artifact, ArtifactProvider, Release,
+};
 
 const BASE_URL: &str = "https://api.github.com";
 
+// NOTE: This is the maximum page size allowed by the GitHub API
+const PAGE_SIZE: usize = 100;
+
 pub mod models;
 mod result;
 
@@ -24,11 +34,12 @@ pub use self::result::{GithubError, GithubResult};
 #[derive(Debug, Clone)]
 pub struct GithubProvider {
     client: ClientWithMiddleware,
+    base_url: String,
     has_auth: bool,
 }
 
 impl GithubProvider {
-    fn new_inner(pat: Option<String>) -> GithubResult<Self> {
+    fn new_inner(pat: Option<String>, options: &ClientOptions) -> GithubResult<Self> {
         let has_auth = pat.is_some();
         let headers = {
             let mut headers = HeaderMap::new();
@@ -43,9 +54,16 @@ impl GithubProvider {
             headers
         };
 
-        let client = create_client(headers)?;
+        let client = create_client(headers, options)?;
+        let base_url = options
+            .base_url_or(ArtifactProvider::GitHub, BASE_URL)
+            .to_string();
 
-        Ok(Self { client, has_auth })
+        Ok(Self {
+            client,
+            base_url,
+            has_auth,
+        })
     }
 
     async fn get_json<T: DeserializeOwned>(&self, url: &str) -> GithubResult<T> {
@@ -54,21 +72,34 @@ impl GithubProvider {
             .get(url)
             .header(ACCEPT, "application/vnd.github.v3+json")
             .send()
-            .await?
-            .error_for_status()?;
+            .await?;
+        if let Some(reset_at) = rate_limit_reset(response.status(), response.headers()) {
+            return Err(GithubError::RateLimited { reset_at });
+        }
+        if let Some((status, url)) = failed_status(&response) {
+            return Err(GithubError::Http { status, url });
+        }
         Ok(response.json().await?)
     }
 
-    async fn get_bytes(&self, url: &str) -> GithubResult<Vec<u8>> {
+    async fn get_bytes(
+        &self,
+        url: &str,
+        progress: &mut (dyn FnMut(u64, Option<u64>) + Send),
+    ) -> GithubResult<Vec<u8>> {
         let response = self
             .client
             .get(url)
             .header(ACCEPT, HeaderValue::from_static("application/octet-stream"))
             .send()
-            .await?
-            .error_for_status()?;
-        let bytes = response.bytes().await.map(|bytes| bytes.to_vec());
-        Ok(bytes?)
+            .await?;
+        if let Some(reset_at) = rate_limit_reset(response.status(), response.headers()) {
+            return Err(GithubError::RateLimited { reset_at });
+        }
+        if let Some((status, url)) = failed_status(&response) {
+            return Err(GithubError::Http { status, url });
+        }
+        Ok(read_body_with_progress(response, progress).await?)
     }
 
     /**
@@ -79,7 +110,7 @@ impl GithubProvider {
         - If the GitHub API client could not be created.
     */
     pub fn new() -> GithubResult<Self> {
-        Self::new_inner(None)
+        Self::new_inner(None, &ClientOptions::default())
     }
 
     /**
@@ -94,7 +125,20 @@ impl GithubProvider {
     */
     pub fn new_authenticated(pat: impl AsRef<str>) -> GithubResult<Self> {
         let pat: String = pat.as_ref().trim().to_string();
-        Self::new_inner(Some(pat))
+        Self::new_inner(Some(pat), &ClientOptions::default())
+    }
+
+    /**
+        Creates a new GitHub source instance with the given client
+        options, and optionally authenticated with a token.
+
+        # Errors
+
+        - If the GitHub API client could not be created.
+    */
+    pub fn new_with_options(pat: Option<&str>, options: &ClientOptions) -> GithubResult<Self> {
+        let pat = pat.map(|pat| pat.trim().to_string());
+        Self::new_inner(pat, options)
     }
 
     /**
@@ -113,7 +157,7 @@ impl GithubProvider {
             return Ok(false);
         }
 
-        let url = format!("{BASE_URL}/rate_limit");
+        let url = format!("{}/rate_limit", self.base_url);
         let res = self.get_json::<serde_json::Value>(&url).await;
 
         match res {
@@ -131,7 +175,8 @@ impl GithubProvider {
         debug!(id = %tool_id, "fetching latest release for tool");
 
         let url = format!(
-            "{BASE_URL}/repos/{owner}/{repo}/releases/latest",
+            "{base_url}/repos/{owner}/{repo}/releases/latest",
+            base_url = self.base_url,
             owner = tool_id.author(),
             repo = tool_id.name(),
         );
@@ -157,6 +202,36 @@ impl GithubProvider {
         })
     }
 
+    /**
+        Fetches all published versions for a given tool, in descending order.
+
+        Releases with tags that are not valid semver versions are skipped.
+    */
+    #[instrument(skip(self), fields(%tool_id), level = "debug")]
+    pub async fn list_versions(&self, tool_id: &ToolId) -> GithubResult<Vec<Version>> {
+        debug!(id = %tool_id, "fetching all versions for tool");
+
+        let releases = fetch_all_pages(PAGE_SIZE, |page| {
+            let url = format!(
+                "{base_url}/repos/{owner}/{repo}/releases?per_page={PAGE_SIZE}&page={page}",
+                base_url = self.base_url,
+                owner = tool_id.author(),
+                repo = tool_id.name(),
+            );
+            async move {
+                match self.get_json::<Vec<GithubRelease>>(&url).await {
+                    Err(e) if is_404(&e) => Err(GithubError::ToolNotFound(tool_id.clone().into())),
+                    result => result,
+                }
+            }
+        })
+        .await?;
+
+        Ok(versions_from_tags(
+            releases.iter().map(|release| release.tag_name.as_str()),
+        ))
+    }
+
     /**
         Fetches a specific release for a given tool.
     */
@@ -165,13 +240,15 @@ impl GithubProvider {
         debug!(spec = %tool_spec, "fetching release for tool");
 
         let url_with_prefix = format!(
-            "{BASE_URL}/repos/{owner}/{repo}/releases/tags/v{tag}",
+            "{base_url}/repos/{owner}/{repo}/releases/tags/v{tag}",
+            base_url = self.base_url,
             owner = tool_spec.author(),
             repo = tool_spec.name(),
             tag = tool_spec.version(),
         );
         let url_without_prefix = format!(
-            "{BASE_URL}/repos/{owner}/{repo}/releases/tags/{tag}",
+            "{base_url}/repos/{owner}/{repo}/releases/tags/{tag}",
+            base_url = self.base_url,
             owner = tool_spec.author(),
             repo = tool_spec.name(),
             tag = tool_spec.version(),
@@ -195,11 +272,68 @@ impl GithubProvider {
         })
     }
 
+    /**
+        Fetches a release for a given tool by its literal tag name.
+
+        Unlike `get_specific_release`, the tag does not need to be
+        a valid semver version, and is not prefixed with a `v`.
+    */
+    #[instrument(skip(self), fields(%tool_id), level = "debug")]
+    pub async fn get_release_by_tag(&self, tool_id: &ToolId, tag: &str) -> GithubResult<Release> {
+        debug!(id = %tool_id, tag, "fetching release by tag for tool");
+
+        let url = format!(
+            "{base_url}/repos/{owner}/{repo}/releases/tags/{tag}",
+            base_url = self.base_url,
+            owner = tool_id.author(),
+            repo = tool_id.name(),
+            tag = encode_path_segment(tag),
+        );
+
+        let release: GithubRelease = match self.get_json(&url).await {
+            Err(e) if is_404(&e) => {
+                return Err(GithubError::ReleaseTagNotFound {
+                    tag: tag.to_string(),
+                });
+            }
+            Err(e) => return Err(e),
+            Ok(r) => r,
+        };
+
+        let version = Release::version_from_tag(&release.tag_name);
+        let tool_spec: ToolSpec = (tool_id.clone(), version).into();
+        Ok(Release {
+            changelog: release.changelog.clone(),
+            artifacts: artifacts_from_release(&release, &tool_spec),
+        })
+    }
+
     /**
         Downloads the contents of the given artifact.
+
+        # Errors
+
+        - If the request to the GitHub API failed.
     */
-    #[instrument(skip(self, artifact), level = "debug")]
     pub async fn download_artifact_contents(&self, artifact: &Artifact) -> GithubResult<Vec<u8>> {
+        self.download_artifact_contents_with_progress(artifact, |_, _| {})
+            .await
+    }
+
+    /**
+        Downloads the contents of the given artifact, calling the given callback
+        with the number of bytes downloaded so far, and the total size, if known.
+
+        # Errors
+
+        - If the request to the GitHub API failed.
+    */
+    #[instrument(skip(self, artifact, progress), level = "debug")]
+    pub async fn download_artifact_contents_with_progress(
+        &self,
+        artifact: &Artifact,
+        mut progress: impl FnMut(u64, Option<u64>) + Send,
+    ) -> GithubResult<Vec<u8>> {
         assert_eq!(
             artifact.provider,
             ArtifactProvider::GitHub,
@@ -211,31 +345,26 @@ impl GithubProvider {
         debug!(id, name, "downloading artifact contents");
 
         let url = format!(
-            "{BASE_URL}/repos/{owner}/{repo}/releases/assets/{id}",
+            "{base_url}/repos/{owner}/{repo}/releases/assets/{id}",
+            base_url = self.base_url,
             owner = artifact.tool_spec.author(),
             repo = artifact.tool_spec.name(),
         );
 
-        self.get_bytes(&url).await
+        self.get_bytes(&url, &mut progress).await
     }
 }
 
 fn is_404(err: &GithubError) -> bool {
-    if let GithubError::Reqwest(reqwest_err) = err {
-        if let Some(status) = reqwest_err.status() {
-            return status == StatusCode::NOT_FOUND;
-        }
-    }
-    false
+    matches!(err, GithubError::Http { status, .. } if *status == StatusCode::NOT_FOUND)
 }
 
 fn is_unauthenticated(err: &GithubError) -> bool {
-    if let GithubError::Reqwest(reqwest_err) = err {
-        if let Some(status) = reqwest_err.status() {
-            return matches!(status, StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN);
-        }
-    }
-    false
+    matches!(
+        err,
+        GithubError::Http { status, .. }
+            if *status == StatusCode::UNAUTHORIZED || *status == StatusCode::FORBIDDEN
+    )
 }
 
 fn artifacts_from_release(release: &GithubRelease, spec: &ToolSpec) -> Vec<Artifact> {
@@ -245,3 +374,358 @@ fn artifacts_from_release(release: &GithubRelease, spec: &ToolSpec) -> Vec<Artif
         .map(|asset| Artifact::from_github_release_asset(asset, spec))
         .collect::<Vec<_>>()
 }
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+    use wiremock::{
+        matchers::{method, path, query_param},
+        Mock, MockServer, ResponseTemplate,
+    };
+
+    use crate::{result::RokitError, sources::test_options};
+
+    use super::*;
+
+    #[tokio::test]
+    async fn custom_base_url() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/api/v3/repos/author/name/releases/latest"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "assets": [{
+                    "id": 1,
+                    "url": format!("{}/api/v3/repos/author/name/releases/assets/1", server.uri()),
+                    "name": "name-1.0.0-linux-x86_64.zip",
+                }],
+                "tag_name": "v1.0.0",
+                "prerelease": false,
+                "body": null,
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let options = test_options(
+            ArtifactProvider::GitHub,
+            format!("{}/api/v3/", server.uri()),
+        );
+        let provider = GithubProvider::new_with_options(None, &options).unwrap();
+
+        let id = "author/name".parse::<ToolId>().unwrap();
+        let release = provider.get_latest_release(&id).await.unwrap();
+        assert_eq!(release.artifacts.len(), 1);
+        assert_eq!(
+            release.artifacts[0].tool_spec.version(),
+            &Version::new(1, 0, 0)
+        );
+    }
+
+    #[tokio::test]
+    async fn latest_release_single_request() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/repos/author/name/releases/latest"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "assets": [{
+                    "id": 1,
+                    "url": format!("{}/repos/author/name/releases/assets/1", server.uri()),
+                    "name": "name-2.0.0-linux-x86_64.zip",
+                }],
+                "tag_name": "v2.0.0",
+                "prerelease": false,
+                "body": null,
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+        // The latest release should never be found by listing all releases
+        Mock::given(method("GET"))
+            .and(path("/repos/author/name/releases"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!([])))
+            .expect(0)
+            .mount(&server)
+            .await;
+
+        let options = test_options(ArtifactProvider::GitHub, server.uri());
+        let provider = GithubProvider::new_with_options(None, &options).unwrap();
+
+        let id = "author/name".parse::<ToolId>().unwrap();
+        let release = provider.get_latest_release(&id).await.unwrap();
+        assert_eq!(
+            release.artifacts[0].tool_spec.version(),
+            &Version::new(2, 0, 0)
+        );
+        assert_eq!(server.received_requests().await.unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn explicit_proxy() {
+        let proxy = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/repos/author/name/releases/latest"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "assets": [],
+                "tag_name": "v1.0.0",
+                "prerelease": false,
+                "body": null,
+            })))
+            .expect(1)
+            .mount(&proxy)
+            .await;
+
+        // The API host does not exist, so this only succeeds if proxied
+        let options = ClientOptions {
+            proxy: Some(proxy.uri()),
+            ..test_options(ArtifactProvider::GitHub, "http://api.rokit.invalid".into())
+        };
+        let provider = GithubProvider::new_with_options(None, &options).unwrap();
+
+        let id = "author/name".parse::<ToolId>().unwrap();
+        provider.get_latest_release(&id).await.unwrap();
+
+        let requests = proxy.received_requests().await.unwrap();
+        assert_eq!(requests.len(), 1);
+        assert_eq!(requests[0].url.host_str(), Some("api.rokit.invalid"));
+    }
+
+    #[tokio::test]
+    async fn release_by_tag() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/repos/author/name/releases/tags/nightly"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "assets": [],
+                "tag_name": "nightly",
+                "prerelease": true,
+                "body": "Nightly build",
+            })))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/repos/author/name/releases/tags/missing"))
+            .respond_with(ResponseTemplate::new(404))
+            .mount(&server)
+            .await;
+
+        let options = test_options(ArtifactProvider::GitHub, server.uri());
+        let provider = GithubProvider::new_with_options(None, &options).unwrap();
+
+        let id = "author/name".parse::<ToolId>().unwrap();
+        let release = provider.get_release_by_tag(&id, "nightly").await.unwrap();
+        assert_eq!(release.changelog.as_deref(), Some("Nightly build"));
+
+        let err = provider
+            .get_release_by_tag(&id, "missing")
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            RokitError::from(err),
+            RokitError::ReleaseNotFound { tag } if tag == "missing"
+        ));
+    }
+
+    #[tokio::test]
+    async fn release_by_tag_with_space() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/repos/author/name/releases/tags/release%201"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "assets": [],
+                "tag_name": "release 1",
+                "prerelease": false,
+                "body": null,
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let options = test_options(ArtifactProvider::GitHub, server.uri());
+        let provider = GithubProvider::new_with_options(None, &options).unwrap();
+
+        // Spaces in tags must be percent-encoded, and never form-encoded as '+'
+        let id = "author/name".parse::<ToolId>().unwrap();
+        provider.get_release_by_tag(&id, "release 1").await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn http_errors() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/repos/author/name/releases/assets/1"))
+            .respond_with(ResponseTemplate::new(404))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/repos/author/name/releases/assets/2"))
+            .respond_with(ResponseTemplate::new(500))
+            .mount(&server)
+            .await;
+
+        let options = ClientOptions {
+            max_retries: 0,
+            ..test_options(ArtifactProvider::GitHub, server.uri())
+        };
+        let provider = GithubProvider::new_with_options(None, &options).unwrap();
+
+        let artifact = |id: &str| Artifact {
+            provider: ArtifactProvider::GitHub,
+            format: None,
+            id: Some(id.to_string()),
+            url: None,
+            name: Some("tool".to_string()),
+            file_name: None,
+            tool_spec: "author/name@1.0.0".parse().unwrap(),
+        };
+
+        let err = provider.download_artifact_contents(&artifact("1")).await;
+        let err = RokitError::from(err.unwrap_err());
+        assert!(err.is_not_found());
+        match err {
+            RokitError::Http { status, url } => {
+                assert_eq!(status, 404);
+                assert!(url.ends_with("/repos/author/name/releases/assets/1"));
+            }
+            other => panic!("expected http error, got {other:?}"),
+        }
+
+        let err = provider.download_artifact_contents(&artifact("2")).await;
+        let err = RokitError::from(err.unwrap_err());
+        assert!(!err.is_not_found());
+        assert!(matches!(err, RokitError::Http { status: 500, .. }));
+    }
+
+    #[tokio::test]
+    async fn download_with_progress() {
+        let contents = (0..=255u8).cycle().take(256 * 1024).collect::<Vec<_>>();
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/repos/author/name/releases/assets/1"))
+            .respond_with(ResponseTemplate::new(200).set_body_bytes(contents.clone()))
+            .mount(&server)
+            .await;
+
+        let options = test_options(ArtifactProvider::GitHub, server.uri());
+        let provider = GithubProvider::new_with_options(None, &options).unwrap();
+
+        let artifact = Artifact {
+            provider: ArtifactProvider::GitHub,
+            format: None,
+            id: Some("1".to_string()),
+            url: None,
+            name: Some("tool".to_string()),
+            file_name: None,
+            tool_spec: "author/name@1.0.0".parse().unwrap(),
+        };
+
+        let mut reported = Vec::new();
+        let downloaded = provider
+            .download_artifact_contents_with_progress(&artifact, |downloaded, total| {
+                reported.push((downloaded, total));
+            })
+            .await
+            .unwrap();
+        assert_eq!(downloaded, contents);
+
+        // Byte counts should increase with every chunk, up to the total size
+        let total = contents.len() as u64;
+        assert!(!reported.is_empty());
+        assert!(reported.windows(2).all(|w| w[0].0 < w[1].0));
+        assert!(reported.iter().all(|(_, t)| *t == Some(total)));
+        assert_eq!(reported.last().unwrap().0, total);
+    }
+
+    #[tokio::test]
+    async fn list_versions_sorted_and_filtered() {
+        let server = MockServer::start().await;
+        let release = |tag: &str| {
+            json!({
+                "assets": [],
+                "tag_name": tag,
+                "prerelease": false,
+                "body": null,
+            })
+        };
+        Mock::given(method("GET"))
+            .and(path("/repos/author/name/releases"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!([
+                release("v1.0.0"),
+                release("nightly"),
+                release("v2.1.0"),
+                release("1.5.0"),
+                release("v2.0.0-rc.1"),
+                release("release-3"),
+            ])))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let options = test_options(ArtifactProvider::GitHub, server.uri());
+        let provider = GithubProvider::new_with_options(None, &options).unwrap();
+
+        let id = "author/name".parse::<ToolId>().unwrap();
+        let versions = provider.list_versions(&id).await.unwrap();
+        let versions = versions.iter().map(ToString::to_string).collect::<Vec<_>>();
+        assert_eq!(versions, ["2.1.0", "2.0.0-rc.1", "1.5.0", "1.0.0"]);
+    }
+
+    #[tokio::test]
+    async fn list_versions_all_pages() {
+        let server = MockServer::start().await;
+        let release = |tag: String| {
+            json!({
+                "assets": [],
+                "tag_name": tag,
+                "prerelease": false,
+                "body": null,
+            })
+        };
+        let first_page = (0..PAGE_SIZE)
+            .map(|minor| release(format!("v1.{minor}.0")))
+            .collect::<Vec<_>>();
+        Mock::given(method("GET"))
+            .and(path("/repos/author/name/releases"))
+            .and(query_param("page", "1"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(first_page))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/repos/author/name/releases"))
+            .and(query_param("page", "2"))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_json(json!([release("v2.0.0".into())])),
+            )
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let options = test_options(ArtifactProvider::GitHub, server.uri());
+        let provider = GithubProvider::new_with_options(None, &options).unwrap();
+
+        let id = "author/name".parse::<ToolId>().unwrap();
+        let versions = provider.list_versions(&id).await.unwrap();
+        assert_eq!(versions.len(), PAGE_SIZE + 1);
+        assert_eq!(versions.first().unwrap().to_string(), "2.0.0");
+        assert_eq!(versions.last().unwrap().to_string(), "1.0.0");
+    }
+
+    #[tokio::test]
+    async fn list_versions_tool_not_found() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/repos/author/missing/releases"))
+            .respond_with(ResponseTemplate::new(404))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let options = test_options(ArtifactProvider::GitHub, server.uri());
+        let provider = GithubProvider::new_with_options(None, &options).unwrap();
+
+        let id = "author/missing".parse::<ToolId>().unwrap();
+        let err = provider.list_versions(&id).await.unwrap_err();
+        assert!(matches!(err, GithubError::ToolNotFound(_)));
+        assert!(RokitError::from(err).is_not_found());
+    }
+}
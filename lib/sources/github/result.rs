@@ -7,10 +7,18 @@ use crate::tool::{ToolId, ToolSpec};
 pub enum GithubError {
     #[error("unrecognized access token format - must begin with `ghp_` or `gho_`.")]
     UnrecognizedAccessToken,
+    #[error("no tool was found with id '{0}'")]
+    ToolNotFound(Box<ToolId>),
     #[error("no latest release was found for tool '{0}'")]
     LatestReleaseNotFound(Box<ToolId>),
     #[error("no release was found for tool '{0}'")]
     ReleaseNotFound(Box<ToolSpec>),
+    #[error("no release was found with tag '{tag}'")]
+    ReleaseTagNotFound { tag: String },
+    #[error("rate limit exceeded - resets at unix time {reset_at}")]
+    RateLimited { reset_at: u64 },
+    #[error("request to '{url}' failed with HTTP status {status}")]
+    Http { status: u16, url: String },
     #[error("failed to build client - invalid header value: {0}")]
     ReqwestHeader(Box<InvalidHeaderValue>),
     #[error("reqwest middleware error: {0}")]
@@ -0,0 +1,534 @@
+use reqwest_middleware::ClientWithMiddleware;
+use semver::Version;
+use serde::de::DeserializeOwned;
+use tracing::{debug, instrument};
+
+use reqwest::{
+    header::{HeaderMap, HeaderValue, ACCEPT, AUTHORIZATION},
+    StatusCode,
+};
+
+use crate::tool::{ToolId, ToolSpec};
+
+use super::{
+    client::{
+        create_client, encode_path_segment, failed_status, rate_limit_reset,
+        read_body_with_progress, ClientOptions,
+    },
+    paging::{fetch_all_pages, versions_from_tags},
+    Artifact, ArtifactProvider, Release,
+};
+
+const BASE_URL: &str = "https://gitlab.com/api/v4";
+
+// NOTE: This is the maximum page size allowed by the GitLab API
+const PAGE_SIZE: usize = 100;
+
+pub mod models;
+mod result;
+
+use self::models::GitlabRelease;
+
+pub use self::result::{GitlabError, GitlabResult};
+
+#[derive(Debug, Clone)]
+pub struct GitlabProvider {
+    client: ClientWithMiddleware,
+    base_url: String,
+    has_auth: bool,
+}
+
+impl GitlabProvider {
+    fn new_inner(pat: Option<String>, options: &ClientOptions) -> GitlabResult<Self> {
+        let has_auth = pat.is_some();
+        let headers = {
+            let mut headers = HeaderMap::new();
+            if let Some(pat) = pat {
+                let token = format!("Bearer {pat}");
+                headers.insert(AUTHORIZATION, HeaderValue::from_str(&token)?);
+            }
+            headers
+        };
+
+        let client = create_client(headers, options)?;
+        let base_url = options
+            .base_url_or(ArtifactProvider::GitLab, BASE_URL)
+            .to_string();
+
+        Ok(Self {
+            client,
+            base_url,
+            has_auth,
+        })
+    }
+
+    async fn get_json<T: DeserializeOwned>(&self, url: &str) -> GitlabResult<T> {
+        let response = self
+            .client
+            .get(url)
+            .header(ACCEPT, "application/json")
+            .send()
+            .await?;
+        if let Some(reset_at) = rate_limit_reset(response.status(), response.headers()) {
+            return Err(GitlabError::RateLimited { reset_at });
+        }
+        if let Some((status, url)) = failed_status(&response) {
+            return Err(GitlabError::Http { status, url });
+        }
+        Ok(response.json().await?)
+    }
+
+    async fn get_bytes(
+        &self,
+        url: &str,
+        progress: &mut (dyn FnMut(u64, Option<u64>) + Send),
+    ) -> GitlabResult<Vec<u8>> {
+        let response = self
+            .client
+            .get(url)
+            .header(ACCEPT, HeaderValue::from_static("application/octet-stream"))
+            .send()
+            .await?;
+        if let Some(reset_at) = rate_limit_reset(response.status(), response.headers()) {
+            return Err(GitlabError::RateLimited { reset_at });
+        }
+        if let Some((status, url)) = failed_status(&response) {
+            return Err(GitlabError::Http { status, url });
+        }
+        Ok(read_body_with_progress(response, progress).await?)
+    }
+
+    /**
+        Creates a new GitLab source instance.
+
+        # Errors
+
+        - If the GitLab API client could not be created.
+    */
+    pub fn new() -> GitlabResult<Self> {
+        Self::new_inner(None, &ClientOptions::default())
+    }
+
+    /**
+        Creates a new authenticated GitLab source instance with a token.
+
+        Note that this does not verify the formatting or validity of the token,
+        use the `verify_authentication` method for checking with the GitLab API.
+
+        # Errors
+
+        - If the GitLab API client could not be created.
+    */
+    pub fn new_authenticated(pat: impl AsRef<str>) -> GitlabResult<Self> {
+        let pat: String = pat.as_ref().trim().to_string();
+        Self::new_inner(Some(pat), &ClientOptions::default())
+    }
+
+    /**
+        Creates a new GitLab source instance with the given client
+        options, and optionally authenticated with a token.
+
+        # Errors
+
+        - If the GitLab API client could not be created.
+    */
+    pub fn new_with_options(pat: Option<&str>, options: &ClientOptions) -> GitlabResult<Self> {
+        let pat = pat.map(|pat| pat.trim().to_string());
+        Self::new_inner(pat, options)
+    }
+
+    /**
+        Verifies that the current authentication token is valid.
+
+        Returns `true` if the token is valid, `false` if it is not.
+
+        Always returns `false` if the source is not authenticated.
+
+        # Errors
+
+        - If the request to the GitLab API failed.
+    */
+    pub async fn verify_authentication(&self) -> GitlabResult<bool> {
+        if !self.has_auth {
+            return Ok(false);
+        }
+
+        let url = format!("{}/user", self.base_url);
+        let res = self.get_json::<serde_json::Value>(&url).await;
+
+        match res {
+            Ok(_) => Ok(true),
+            Err(e) if is_unauthenticated(&e) => Ok(false),
+            Err(e) => Err(e),
+        }
+    }
+
+    /**
+        Fetches the latest release for a given tool.
+    */
+    #[instrument(skip(self), fields(%tool_id), level = "debug")]
+    pub async fn get_latest_release(&self, tool_id: &ToolId) -> GitlabResult<Release> {
+        debug!(id = %tool_id, "fetching latest release for tool");
+
+        let url = format!(
+            "{base_url}/projects/{project}/releases/permalink/latest",
+            base_url = self.base_url,
+            project = project_path(tool_id),
+        );
+
+        let release: GitlabRelease = match self.get_json(&url).await {
+            Err(e) if is_404(&e) => {
+                return Err(GitlabError::LatestReleaseNotFound(tool_id.clone().into()));
+            }
+            Err(e) => return Err(e),
+            Ok(r) => r,
+        };
+
+        let version = release
+            .tag_name
+            .trim_start_matches('v')
+            .parse::<Version>()
+            .map_err(|e| GitlabError::Other(e.to_string()))?;
+
+        let tool_spec: ToolSpec = (tool_id.clone(), version).into();
+        Ok(Release {
+            changelog: release.changelog.clone(),
+            artifacts: artifacts_from_release(&release, &tool_spec),
+        })
+    }
+
+    /**
+        Fetches all published versions for a given tool, in descending order.
+
+        Upcoming releases, which have a release date in the future, and
+        releases with tags that are not valid semver versions are skipped.
+    */
+    #[instrument(skip(self), fields(%tool_id), level = "debug")]
+    pub async fn list_versions(&self, tool_id: &ToolId) -> GitlabResult<Vec<Version>> {
+        debug!(id = %tool_id, "fetching all versions for tool");
+
+        let releases = fetch_all_pages(PAGE_SIZE, |page| {
+            let url = format!(
+                "{base_url}/projects/{project}/releases?per_page={PAGE_SIZE}&page={page}",
+                base_url = self.base_url,
+                project = project_path(tool_id),
+            );
+            async move {
+                match self.get_json::<Vec<GitlabRelease>>(&url).await {
+                    Err(e) if is_404(&e) => Err(GitlabError::ToolNotFound(tool_id.clone().into())),
+                    result => result,
+                }
+            }
+        })
+        .await?;
+
+        Ok(versions_from_tags(
+            releases
+                .iter()
+                .filter(|release| !release.upcoming_release)
+                .map(|release| release.tag_name.as_str()),
+        ))
+    }
+
+    /**
+        Fetches a specific release for a given tool.
+    */
+    #[instrument(skip(self), fields(%tool_spec), level = "debug")]
+    pub async fn get_specific_release(&self, tool_spec: &ToolSpec) -> GitlabResult<Release> {
+        debug!(spec = %tool_spec, "fetching release for tool");
+
+        let url_with_prefix = format!(
+            "{base_url}/projects/{project}/releases/v{tag}",
+            base_url = self.base_url,
+            project = project_path(tool_spec.id()),
+            tag = tool_spec.version(),
+        );
+        let url_without_prefix = format!(
+            "{base_url}/projects/{project}/releases/{tag}",
+            base_url = self.base_url,
+            project = project_path(tool_spec.id()),
+            tag = tool_spec.version(),
+        );
+
+        let release: GitlabRelease = match self.get_json(&url_with_prefix).await {
+            Err(e) if is_404(&e) => match self.get_json(&url_without_prefix).await {
+                Err(e) if is_404(&e) => {
+                    return Err(GitlabError::ReleaseNotFound(tool_spec.clone().into()));
+                }
+                Err(e) => return Err(e),
+                Ok(r) => r,
+            },
+            Err(e) => return Err(e),
+            Ok(r) => r,
+        };
+
+        Ok(Release {
+            changelog: release.changelog.clone(),
+            artifacts: artifacts_from_release(&release, tool_spec),
+        })
+    }
+
+    /**
+        Fetches a release for a given tool by its literal tag name.
+
+        Unlike `get_specific_release`, the tag does not need to be
+        a valid semver version, and is not prefixed with a `v`.
+    */
+    #[instrument(skip(self), fields(%tool_id), level = "debug")]
+    pub async fn get_release_by_tag(&self, tool_id: &ToolId, tag: &str) -> GitlabResult<Release> {
+        debug!(id = %tool_id, tag, "fetching release by tag for tool");
+
+        let url = format!(
+            "{base_url}/projects/{project}/releases/{tag}",
+            base_url = self.base_url,
+            project = project_path(tool_id),
+            tag = encode_path_segment(tag),
+        );
+
+        let release: GitlabRelease = match self.get_json(&url).await {
+            Err(e) if is_404(&e) => {
+                return Err(GitlabError::ReleaseTagNotFound {
+                    tag: tag.to_string(),
+                });
+            }
+            Err(e) => return Err(e),
+            Ok(r) => r,
+        };
+
+        let version = Release::version_from_tag(&release.tag_name);
+        let tool_spec: ToolSpec = (tool_id.clone(), version).into();
+        Ok(Release {
+            changelog: release.changelog.clone(),
+            artifacts: artifacts_from_release(&release, &tool_spec),
+        })
+    }
+
+    /**
+        Downloads the contents of the given artifact.
+
+        # Errors
+
+        - If the request to the GitLab API failed.
+    */
+    pub async fn download_artifact_contents(&self, artifact: &Artifact) -> GitlabResult<Vec<u8>> {
+        self.download_artifact_contents_with_progress(artifact, |_, _| {})
+            .await
+    }
+
+    /**
+        Downloads the contents of the given artifact, calling the given callback
+        with the number of bytes downloaded so far, and the total size, if known.
+
+        # Errors
+
+        - If the request to the GitLab API failed.
+    */
+    #[instrument(skip(self, artifact, progress), level = "debug")]
+    pub async fn download_artifact_contents_with_progress(
+        &self,
+        artifact: &Artifact,
+        mut progress: impl FnMut(u64, Option<u64>) + Send,
+    ) -> GitlabResult<Vec<u8>> {
+        assert_eq!(
+            artifact.provider,
+            ArtifactProvider::GitLab,
+            "artifact must be from GitLab"
+        );
+
+        let id = artifact.id.as_ref().expect("GitLab artifacts have ids");
+        let url = artifact.url.as_ref().expect("GitLab artifacts have urls");
+        debug!(id, %url, "downloading artifact contents");
+
+        self.get_bytes(url.as_str(), &mut progress).await
+    }
+}
+
+/**
+    Formats the URL-encoded project path for a tool, which is
+    accepted by the GitLab API in place of a numeric project id.
+
+    Authors may be nested groups on GitLab, but since our tool ids only
+    support a single author segment, only top-level groups are supported.
+*/
+fn project_path(tool_id: &ToolId) -> String {
+    format!("{}%2F{}", tool_id.author(), tool_id.name())
+}
+
+fn is_404(err: &GitlabError) -> bool {
+    matches!(err, GitlabError::Http { status, .. } if *status == StatusCode::NOT_FOUND)
+}
+
+fn is_unauthenticated(err: &GitlabError) -> bool {
+    matches!(
+        err,
+        GitlabError::Http { status, .. }
+            if *status == StatusCode::UNAUTHORIZED || *status == StatusCode::FORBIDDEN
+    )
+}
+
+fn artifacts_from_release(release: &GitlabRelease, spec: &ToolSpec) -> Vec<Artifact> {
+    release
+        .assets
+        .links
+        .iter()
+        .map(|link| Artifact::from_gitlab_release_link(link, spec))
+        .collect::<Vec<_>>()
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{json, Value};
+    use wiremock::{
+        matchers::{header, method, path},
+        Mock, MockServer, ResponseTemplate,
+    };
+
+    use crate::sources::test_options;
+
+    use super::*;
+
+    fn release(server: &MockServer, tag: &str) -> Value {
+        json!({
+            "assets": {
+                "links": [{
+                    "id": 1,
+                    "url": format!("{}/author/name/-/releases/{tag}/downloads/name.zip", server.uri()),
+                    "direct_asset_url": format!("{}/downloads/name-{tag}-linux-x86_64.zip", server.uri()),
+                    "name": format!("name-{tag}-linux-x86_64.zip"),
+                }],
+            },
+            "tag_name": tag,
+            "upcoming_release": false,
+            "description": "Changelog",
+        })
+    }
+
+    fn provider(server: &MockServer) -> GitlabProvider {
+        let options = test_options(ArtifactProvider::GitLab, server.uri());
+        GitlabProvider::new_with_options(None, &options).unwrap()
+    }
+
+    #[tokio::test]
+    async fn latest_release() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/projects/author%2Fname/releases/permalink/latest"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(release(&server, "v1.2.0")))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let id = "gitlab:author/name".parse::<ToolId>().unwrap();
+        let release = provider(&server).get_latest_release(&id).await.unwrap();
+        assert_eq!(release.changelog.as_deref(), Some("Changelog"));
+        assert_eq!(release.artifacts.len(), 1);
+
+        let artifact = &release.artifacts[0];
+        assert_eq!(artifact.provider, ArtifactProvider::GitLab);
+        assert_eq!(artifact.tool_spec.version(), &Version::new(1, 2, 0));
+        assert_eq!(
+            artifact.file_name.as_deref(),
+            Some("name-v1.2.0-linux-x86_64.zip")
+        );
+        // Direct asset URLs are preferred over the release page URL
+        assert_eq!(
+            artifact.url.as_ref().unwrap().as_str(),
+            format!("{}/downloads/name-v1.2.0-linux-x86_64.zip", server.uri())
+        );
+    }
+
+    #[tokio::test]
+    async fn latest_release_not_found() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/projects/author%2Fname/releases/permalink/latest"))
+            .respond_with(ResponseTemplate::new(404))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let id = "gitlab:author/name".parse::<ToolId>().unwrap();
+        let err = provider(&server).get_latest_release(&id).await.unwrap_err();
+        assert!(matches!(err, GitlabError::LatestReleaseNotFound(_)));
+    }
+
+    #[tokio::test]
+    async fn release_by_tag() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/projects/author%2Fname/releases/nightly%202"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(release(&server, "nightly 2")))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/projects/author%2Fname/releases/missing"))
+            .respond_with(ResponseTemplate::new(404))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let provider = provider(&server);
+        let id = "gitlab:author/name".parse::<ToolId>().unwrap();
+        let release = provider.get_release_by_tag(&id, "nightly 2").await.unwrap();
+        assert_eq!(release.artifacts.len(), 1);
+
+        let err = provider
+            .get_release_by_tag(&id, "missing")
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            GitlabError::ReleaseTagNotFound { tag } if tag == "missing"
+        ));
+    }
+
+    #[tokio::test]
+    async fn list_versions_skips_upcoming() {
+        let server = MockServer::start().await;
+        let mut upcoming = release(&server, "v3.0.0");
+        upcoming["upcoming_release"] = json!(true);
+        Mock::given(method("GET"))
+            .and(path("/projects/author%2Fname/releases"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!([
+                upcoming,
+                release(&server, "v2.0.0"),
+                release(&server, "nightly"),
+                release(&server, "v1.0.0"),
+            ])))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let id = "gitlab:author/name".parse::<ToolId>().unwrap();
+        let versions = provider(&server).list_versions(&id).await.unwrap();
+        let versions = versions.iter().map(ToString::to_string).collect::<Vec<_>>();
+        assert_eq!(versions, ["2.0.0", "1.0.0"]);
+    }
+
+    #[tokio::test]
+    async fn download_artifact() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/projects/author%2Fname/releases/permalink/latest"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(release(&server, "v1.0.0")))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/downloads/name-v1.0.0-linux-x86_64.zip"))
+            .and(header("accept", "application/octet-stream"))
+            .respond_with(ResponseTemplate::new(200).set_body_bytes(b"contents"))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let provider = provider(&server);
+        let id = "gitlab:author/name".parse::<ToolId>().unwrap();
+        let release = provider.get_latest_release(&id).await.unwrap();
+        let contents = provider
+            .download_artifact_contents(&release.artifacts[0])
+            .await
+            .unwrap();
+        assert_eq!(contents, b"contents");
+    }
+}
@@ -0,0 +1,26 @@
+use serde::Deserialize;
+use url::Url;
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct GitlabRelease {
+    pub assets: GitlabReleaseAssets,
+    pub tag_name: String,
+    #[serde(default)]
+    pub upcoming_release: bool,
+    #[serde(rename = "description")]
+    pub changelog: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct GitlabReleaseAssets {
+    #[serde(default)]
+    pub links: Vec<GitlabAssetLink>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct GitlabAssetLink {
+    pub id: u64,
+    pub url: Url,
+    pub direct_asset_url: Option<Url>,
+    pub name: String,
+}
@@ -0,0 +1,50 @@
+use reqwest::{header::InvalidHeaderValue, Error as ReqwestError};
+use thiserror::Error;
+
+use crate::tool::{ToolId, ToolSpec};
+
+#[derive(Debug, Error)]
+pub enum GitlabError {
+    #[error("no tool was found with id '{0}'")]
+    ToolNotFound(Box<ToolId>),
+    #[error("no latest release was found for tool '{0}'")]
+    LatestReleaseNotFound(Box<ToolId>),
+    #[error("no release was found for tool '{0}'")]
+    ReleaseNotFound(Box<ToolSpec>),
+    #[error("no release was found with tag '{tag}'")]
+    ReleaseTagNotFound { tag: String },
+    #[error("rate limit exceeded - resets at unix time {reset_at}")]
+    RateLimited { reset_at: u64 },
+    #[error("request to '{url}' failed with HTTP status {status}")]
+    Http { status: u16, url: String },
+    #[error("failed to build client - invalid header value: {0}")]
+    ReqwestHeader(Box<InvalidHeaderValue>),
+    #[error("reqwest middleware error: {0}")]
+    ReqwestMiddleware(Box<reqwest_middleware::Error>),
+    #[error("reqwest error: {0}")]
+    Reqwest(Box<reqwest::Error>),
+    #[error("other error: {0}")]
+    Other(String),
+}
+
+pub type GitlabResult<T> = Result<T, GitlabError>;
+
+// FUTURE: Figure out some way to reduce this boxing boilerplate
+
+impl From<InvalidHeaderValue> for GitlabError {
+    fn from(err: InvalidHeaderValue) -> Self {
+        GitlabError::ReqwestHeader(err.into())
+    }
+}
+
+impl From<reqwest_middleware::Error> for GitlabError {
+    fn from(err: reqwest_middleware::Error) -> Self {
+        GitlabError::ReqwestMiddleware(err.into())
+    }
+}
+
+impl From<ReqwestError> for GitlabError {
+    fn from(err: ReqwestError) -> Self {
+        GitlabError::Reqwest(err.into())
+    }
+}
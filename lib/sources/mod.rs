@@ -1,11 +1,28 @@
 mod artifact;
+mod bounded;
+mod checksum;
 mod client;
 mod decompression;
 mod extraction;
+mod paging;
+mod plan;
+mod resolved;
 mod source;
 
+pub mod bitbucket;
 pub mod github;
+pub mod gitlab;
 
-pub use self::artifact::{Artifact, ArtifactFormat, ArtifactProvider, Release};
-pub use self::extraction::ExtractError;
+pub use self::artifact::{
+    normalize_asset_name, Artifact, ArtifactFormat, ArtifactProvider, Release,
+};
+pub use self::bounded::DEFAULT_MAX_IN_FLIGHT;
+pub use self::checksum::{find_sha256_in_checksums, sha256_hex, verify_sha256};
+pub use self::client::ClientOptions;
+pub use self::extraction::{ExtractError, ExtractedBinary, ExtractedFile};
+pub use self::plan::InstallPlan;
+pub use self::resolved::ResolvedInstall;
 pub use self::source::ArtifactSource;
+
+#[cfg(test)]
+pub(crate) use self::client::test_options;
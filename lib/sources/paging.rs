@@ -0,0 +1,97 @@
+use std::future::Future;
+
+use semver::Version;
+
+/**
+    Fetches all items from a paginated list, by requesting pages
+    with numbers starting at one, until a page is returned with
+    fewer than `page_size` items in it.
+
+    # Errors
+
+    - If fetching any of the pages failed.
+*/
+pub(crate) async fn fetch_all_pages<T, E, F, Fut>(
+    page_size: usize,
+    mut fetch_page: F,
+) -> Result<Vec<T>, E>
+where
+    F: FnMut(usize) -> Fut,
+    Fut: Future<Output = Result<Vec<T>, E>>,
+{
+    let mut items = Vec::new();
+    for page in 1.. {
+        let page_items = fetch_page(page).await?;
+        let is_last_page = page_items.len() < page_size;
+        items.extend(page_items);
+        if is_last_page {
+            break;
+        }
+    }
+    Ok(items)
+}
+
+/**
+    Parses versions from the given release tags, which may be prefixed
+    with a `v`, and returns them without duplicates, in descending order.
+
+    Tags that are not valid semver versions are skipped.
+*/
+pub(crate) fn versions_from_tags<'a>(tags: impl IntoIterator<Item = &'a str>) -> Vec<Version> {
+    let mut versions = tags
+        .into_iter()
+        .filter_map(|tag| tag.trim_start_matches('v').parse::<Version>().ok())
+        .collect::<Vec<_>>();
+    versions.sort_unstable_by(|a, b| b.cmp(a));
+    versions.dedup();
+    versions
+}
+
+#[cfg(test)]
+mod tests {
+    use std::convert::Infallible;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn fetches_until_short_page() {
+        let mut requested = Vec::new();
+        let items = fetch_all_pages(2, |page| {
+            requested.push(page);
+            let items = match page {
+                1 | 2 => vec![page * 10, page * 10 + 1],
+                _ => vec![page * 10],
+            };
+            async move { Ok::<_, Infallible>(items) }
+        })
+        .await
+        .unwrap();
+        assert_eq!(requested, [1, 2, 3]);
+        assert_eq!(items, [10, 11, 20, 21, 30]);
+    }
+
+    #[tokio::test]
+    async fn stops_at_first_error() {
+        let mut requested = Vec::new();
+        let result = fetch_all_pages(1, |page| {
+            requested.push(page);
+            async move {
+                if page == 2 {
+                    Err("failed")
+                } else {
+                    Ok(vec![page])
+                }
+            }
+        })
+        .await;
+        assert_eq!(result, Err("failed"));
+        assert_eq!(requested, [1, 2]);
+    }
+
+    #[test]
+    fn versions_sorted_and_deduplicated() {
+        let versions = versions_from_tags(["v1.0.0", "nightly", "2.0.0", "v2.0.0", "1.5.0-rc.1"]);
+        let versions = versions.iter().map(ToString::to_string).collect::<Vec<_>>();
+        assert_eq!(versions, ["2.0.0", "1.5.0-rc.1", "1.0.0"]);
+    }
+}
@@ -0,0 +1,314 @@
+use semver::Version;
+
+use crate::{
+    result::{RokitError, RokitResult},
+    tool::{ToolId, ToolSpec, ToolVersion, ToolVersionSpec},
+};
+
+/**
+    A consolidated plan for installing many tools at once, where
+    every tool id appears at most once, resolved to a single version
+    that satisfies all of the requirements that were given for it.
+
+    Tools are kept in the order that they were first requested in.
+*/
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct InstallPlan {
+    pub(crate) specs: Vec<ToolSpec>,
+}
+
+impl InstallPlan {
+    /**
+        Returns the resolved tool specifications in this plan.
+    */
+    #[must_use]
+    pub fn specs(&self) -> &[ToolSpec] {
+        &self.specs
+    }
+
+    /**
+        Returns the number of unique tools in this plan.
+    */
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.specs.len()
+    }
+
+    /**
+        Checks if this plan contains no tools.
+    */
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.specs.is_empty()
+    }
+}
+
+impl IntoIterator for InstallPlan {
+    type Item = ToolSpec;
+    type IntoIter = std::vec::IntoIter<ToolSpec>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.specs.into_iter()
+    }
+}
+
+/**
+    All of the unique requirements that were given for a single tool id.
+*/
+#[derive(Debug, Clone)]
+pub(crate) struct PlannedTool {
+    pub(crate) id: ToolId,
+    pub(crate) versions: Vec<ToolVersion>,
+}
+
+impl PlannedTool {
+    /**
+        Returns the version to install for this tool, if it can be known without
+        fetching the available versions - meaning all requirements are the same
+        exact version. Differing exact versions are reported as a conflict.
+
+        # Errors
+
+        - If two of the requirements are differing exact versions.
+    */
+    pub(crate) fn exact_version(&self) -> RokitResult<Option<Version>> {
+        let mut exact = None::<&Version>;
+        for version in &self.versions {
+            let ToolVersion::Exact(version) = version else {
+                return Ok(None);
+            };
+            match exact {
+                Some(previous) if previous != version => {
+                    return Err(self.conflict(&previous.clone().into(), &version.clone().into()));
+                }
+                _ => exact = Some(version),
+            }
+        }
+        Ok(exact.cloned())
+    }
+
+    /**
+        Resolves the highest available version that satisfies all of the
+        requirements for this tool, and returns it as a tool specification.
+
+        # Errors
+
+        - If no available version satisfies one of the requirements.
+        - If no available version satisfies two of the requirements at once.
+    */
+    pub(crate) fn resolve(&self, available: &[Version]) -> RokitResult<ToolSpec> {
+        let mut candidates = available.iter().collect::<Vec<_>>();
+        for (index, version) in self.versions.iter().enumerate() {
+            if !available.iter().any(|v| version.matches(v)) {
+                return Err(RokitError::NoMatchingVersion {
+                    id: self.id.clone().into(),
+                    version: version.clone().into(),
+                });
+            }
+            candidates.retain(|v| version.matches(v));
+            if candidates.is_empty() {
+                // NOTE: Prefer naming a requirement that is incompatible with this
+                // one on its own, and fall back to the first one, since it may be
+                // a combination of several earlier requirements that conflicts
+                let first = self.versions[..index]
+                    .iter()
+                    .find(|other| {
+                        !available
+                            .iter()
+                            .any(|v| other.matches(v) && version.matches(v))
+                    })
+                    .unwrap_or(&self.versions[0]);
+                return Err(self.conflict(first, version));
+            }
+        }
+        let version = candidates
+            .into_iter()
+            .max()
+            .expect("tools always have at least one requirement");
+        Ok((self.id.clone(), version.clone()).into())
+    }
+
+    fn conflict(&self, first: &ToolVersion, second: &ToolVersion) -> RokitError {
+        RokitError::ConflictingRequirements {
+            id: self.id.clone().into(),
+            first: first.clone().into(),
+            second: second.clone().into(),
+        }
+    }
+}
+
+/**
+    Groups the given requirements by their tool ids, which are compared
+    case-insensitively, removing any duplicate requirements for each id.
+
+    Tools are kept in the order that they were first requested in.
+*/
+pub(crate) fn group_requirements(
+    requirements: impl IntoIterator<Item = ToolVersionSpec>,
+) -> Vec<PlannedTool> {
+    let mut tools = Vec::<PlannedTool>::new();
+    for requirement in requirements {
+        let ToolVersionSpec { id, version } = requirement;
+        match tools.iter_mut().find(|tool| tool.id == id) {
+            Some(tool) if tool.versions.contains(&version) => {}
+            Some(tool) => tool.versions.push(version),
+            None => tools.push(PlannedTool {
+                id,
+                versions: vec![version],
+            }),
+        }
+    }
+    tools
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use serde_json::json;
+    use wiremock::{
+        matchers::{method, path},
+        Mock, MockServer, ResponseTemplate,
+    };
+
+    use crate::sources::{test_options, ArtifactProvider, ArtifactSource};
+
+    use super::*;
+
+    const AVAILABLE: &[&str] = &["1.0.0", "1.2.0", "1.3.0", "2.0.0", "2.1.0"];
+
+    fn available() -> Vec<Version> {
+        AVAILABLE.iter().map(|v| v.parse().unwrap()).collect()
+    }
+
+    fn group(requirements: &[&str]) -> Vec<PlannedTool> {
+        group_requirements(
+            requirements
+                .iter()
+                .map(|requirement| requirement.parse::<ToolVersionSpec>().unwrap()),
+        )
+    }
+
+    fn resolve(requirements: &[&str]) -> RokitResult<String> {
+        let tools = group(requirements);
+        assert_eq!(tools.len(), 1);
+        tools[0].resolve(&available()).map(|spec| spec.to_string())
+    }
+
+    #[test]
+    fn deduplicates_by_id() {
+        let tools = group(&[
+            "author/tool@1.2.0",
+            "Author/Tool@1.2.0",
+            "other/tool@^2",
+            "AUTHOR/TOOL@^1",
+            "other/tool@^2",
+        ]);
+        // Ids are compared case-insensitively, the first casing is kept
+        assert_eq!(tools.len(), 2);
+        assert_eq!(tools[0].id.to_string(), "author/tool");
+        assert_eq!(tools[1].id.to_string(), "other/tool");
+        // ... and duplicate requirements are only kept once
+        assert_eq!(tools[0].versions.len(), 2);
+        assert_eq!(tools[1].versions.len(), 1);
+    }
+
+    #[test]
+    fn exact_versions_skip_resolution() {
+        let tools = group(&["author/tool@1.2.0", "Author/Tool@1.2.0"]);
+        let exact = tools[0].exact_version().unwrap();
+        assert_eq!(exact, Some(Version::new(1, 2, 0)));
+
+        let tools = group(&["author/tool@1.2.0", "author/tool@^1"]);
+        assert_eq!(tools[0].exact_version().unwrap(), None);
+    }
+
+    #[test]
+    fn merges_compatible_requirements() {
+        assert_eq!(
+            resolve(&["author/tool@^1", "author/tool@>=1.1"]).unwrap(),
+            "author/tool@1.3.0"
+        );
+        assert_eq!(
+            resolve(&["author/tool@>=1", "author/tool@<2", "author/tool@~1.2"]).unwrap(),
+            "author/tool@1.2.0"
+        );
+        assert_eq!(
+            resolve(&["author/tool@^1", "author/tool@1.2.0"]).unwrap(),
+            "author/tool@1.2.0"
+        );
+        assert_eq!(resolve(&["author/tool@*"]).unwrap(), "author/tool@2.1.0");
+    }
+
+    #[test]
+    fn reports_incompatible_requirements() {
+        let conflict = |requirements: &[&str]| match resolve(requirements) {
+            Err(RokitError::ConflictingRequirements { id, first, second }) => {
+                (id.to_string(), first.to_string(), second.to_string())
+            }
+            other => panic!("expected conflicting requirements, got {other:?}"),
+        };
+        assert_eq!(
+            conflict(&["author/tool@^1", "author/tool@^2"]),
+            ("author/tool".into(), "^1".into(), "^2".into())
+        );
+        // The requirement that is disjoint on its own should be named
+        assert_eq!(
+            conflict(&["author/tool@>=1", "author/tool@<2", "author/tool@2.1.0"]),
+            ("author/tool".into(), "<2".into(), "2.1.0".into())
+        );
+
+        let tools = group(&["author/tool@1.2.0", "author/tool@1.3.0"]);
+        assert!(matches!(
+            tools[0].exact_version(),
+            Err(RokitError::ConflictingRequirements { .. })
+        ));
+    }
+
+    #[test]
+    fn reports_unsatisfiable_requirements() {
+        assert!(matches!(
+            resolve(&["author/tool@^1", "author/tool@^3"]),
+            Err(RokitError::NoMatchingVersion { version, .. }) if version.to_string() == "^3"
+        ));
+    }
+
+    #[tokio::test]
+    async fn resolves_each_id_once() {
+        let server = MockServer::start().await;
+        let release =
+            |tag: &str| json!({ "assets": [], "tag_name": tag, "prerelease": false, "body": null });
+        Mock::given(method("GET"))
+            .and(path("/repos/author/tool/releases"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!([
+                release("v2.0.0"),
+                release("v1.3.0"),
+                release("v1.0.0"),
+            ])))
+            .expect(1)
+            .mount(&server)
+            .await;
+        // Tools pinned to a single exact version need no requests at all
+        Mock::given(method("GET"))
+            .and(path("/repos/other/pinned/releases"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!([])))
+            .expect(0)
+            .mount(&server)
+            .await;
+
+        let options = test_options(ArtifactProvider::GitHub, server.uri());
+        let source = ArtifactSource::new_with_options(&HashMap::new(), &options).unwrap();
+
+        let requirements = [
+            "author/tool@^1",
+            "other/pinned@3.0.0",
+            "Author/Tool@>=1.1",
+            "other/pinned@3.0.0",
+        ]
+        .map(|requirement| requirement.parse::<ToolVersionSpec>().unwrap());
+        let plan = source.resolve_install_plan(requirements, 8).await.unwrap();
+
+        let specs = plan.into_iter().map(|s| s.to_string()).collect::<Vec<_>>();
+        assert_eq!(specs, ["author/tool@1.3.0", "other/pinned@3.0.0"]);
+    }
+}
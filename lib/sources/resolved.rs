@@ -0,0 +1,155 @@
+use semver::Version;
+use url::Url;
+
+use crate::{descriptor::Descriptor, result::RokitResult, tool::ToolSpec};
+
+use super::{Artifact, ArtifactFormat, Release};
+
+/**
+    A tool that has been resolved for installation, containing the
+    artifact that would be downloaded, and how it was selected.
+
+    Resolving a tool only fetches its release from the artifact provider,
+    and never downloads or extracts any artifact, which makes this useful
+    for previewing installs and for debugging artifact selection.
+*/
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ResolvedInstall {
+    /**
+        The concrete version of the tool that would be installed.
+    */
+    pub version: Version,
+    /**
+        The full file name of the selected asset, including any archive extensions.
+    */
+    pub asset_name: Option<String>,
+    /**
+        The archive format of the selected asset, if known from its name.
+    */
+    pub asset_format: Option<ArtifactFormat>,
+    /**
+        The URL that the selected asset would be downloaded from.
+
+        Always `None` for artifacts that were resolved from the artifact cache.
+    */
+    pub asset_url: Option<Url>,
+    /**
+        The system that the asset was selected for.
+    */
+    pub system: Descriptor,
+    /**
+        The system detected from the name of the selected asset, if any.
+    */
+    pub asset_system: Option<Descriptor>,
+    /**
+        The selected artifact itself.
+    */
+    pub artifact: Artifact,
+}
+
+impl ResolvedInstall {
+    /**
+        Resolves an install from an already fetched release, by selecting
+        the artifact that is most compatible with the given system.
+
+        # Errors
+
+        - If no artifact in the release is compatible with the given system.
+    */
+    pub fn from_release(release: &Release, system: Descriptor) -> RokitResult<Self> {
+        let artifact = Artifact::select_most_compatible(&release.artifacts, system)?;
+        Ok(Self {
+            version: artifact.tool_spec.version().clone(),
+            asset_name: artifact.file_name.clone(),
+            asset_format: artifact.format,
+            asset_url: artifact.url.clone(),
+            system,
+            asset_system: artifact.name.as_deref().and_then(Descriptor::detect),
+            artifact,
+        })
+    }
+
+    /**
+        Returns the tool specification that was resolved.
+    */
+    #[must_use]
+    pub fn spec(&self) -> &ToolSpec {
+        &self.artifact.tool_spec
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use serde_json::json;
+    use wiremock::{
+        matchers::{method, path},
+        Mock, MockServer, ResponseTemplate,
+    };
+
+    use crate::sources::{test_options, ArtifactProvider, ArtifactSource};
+
+    use super::*;
+
+    #[tokio::test]
+    async fn resolves_multi_asset_release() {
+        let server = MockServer::start().await;
+        let asset = |id: u64, name: &str| {
+            json!({
+                "id": id,
+                "url": format!("{}/repos/author/tool/releases/assets/{id}", server.uri()),
+                "name": name,
+            })
+        };
+        Mock::given(method("GET"))
+            .and(path("/repos/author/tool/releases/tags/v1.2.3"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "assets": [
+                    asset(1, "tool-1.2.3-linux-x86_64.zip"),
+                    asset(2, "tool-1.2.3-macos-aarch64.tar.gz"),
+                    asset(3, "tool-1.2.3-windows-x86_64.zip"),
+                ],
+                "tag_name": "v1.2.3",
+                "prerelease": false,
+                "body": null,
+            })))
+            .mount(&server)
+            .await;
+        // Resolving must never download any of the assets
+        Mock::given(method("GET"))
+            .and(path("/repos/author/tool/releases/assets/2"))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(0)
+            .mount(&server)
+            .await;
+
+        let options = test_options(ArtifactProvider::GitHub, server.uri());
+        let source = ArtifactSource::new_with_options(&HashMap::new(), &options).unwrap();
+
+        let spec = "author/tool@1.2.3".parse::<ToolSpec>().unwrap();
+        let system = "macos-aarch64".parse::<Descriptor>().unwrap();
+        let resolved = source.resolve_install(&spec, system).await.unwrap();
+
+        assert_eq!(resolved.spec(), &spec);
+        assert_eq!(resolved.version, Version::new(1, 2, 3));
+        assert_eq!(
+            resolved.asset_name.as_deref(),
+            Some("tool-1.2.3-macos-aarch64.tar.gz")
+        );
+        assert_eq!(resolved.asset_format, Some(ArtifactFormat::TarGz));
+        assert_eq!(
+            resolved.asset_url.unwrap().as_str(),
+            format!("{}/repos/author/tool/releases/assets/2", server.uri())
+        );
+        assert_eq!(resolved.system, system);
+        assert_eq!(resolved.asset_system, Some(system));
+
+        let system = "linux-x86_64".parse::<Descriptor>().unwrap();
+        let resolved = source.resolve_install(&spec, system).await.unwrap();
+        assert_eq!(
+            resolved.asset_name.as_deref(),
+            Some("tool-1.2.3-linux-x86_64.zip")
+        );
+    }
+}
@@ -1,20 +1,29 @@
 use std::collections::HashMap;
 
+use semver::Version;
+
 use crate::{
+    descriptor::Descriptor,
     result::RokitResult,
-    tool::{ToolId, ToolSpec},
+    tool::{ToolId, ToolSpec, ToolVersionSpec},
 };
 
-use super::{github::GithubProvider, Artifact, ArtifactProvider, Release};
+use super::{
+    bitbucket::BitbucketProvider, bounded::run_bounded, client::ClientOptions,
+    github::GithubProvider, gitlab::GitlabProvider, plan::group_requirements, Artifact,
+    ArtifactProvider, InstallPlan, Release, ResolvedInstall,
+};
 
 /**
     A source for artifacts.
 
-    Provides high-level access abstracting over individual providers such as GitHub, ...
+    Provides high-level access abstracting over individual providers such as GitHub, GitLab, ...
 */
 #[derive(Debug, Clone)]
 pub struct ArtifactSource {
     github: GithubProvider,
+    gitlab: GitlabProvider,
+    bitbucket: BitbucketProvider,
 }
 
 impl ArtifactSource {
@@ -30,7 +39,13 @@ impl ArtifactSource {
     */
     pub fn new() -> RokitResult<Self> {
         let github = GithubProvider::new()?;
-        Ok(Self { github })
+        let gitlab = GitlabProvider::new()?;
+        let bitbucket = BitbucketProvider::new()?;
+        Ok(Self {
+            github,
+            gitlab,
+            bitbucket,
+        })
     }
 
     /**
@@ -43,11 +58,31 @@ impl ArtifactSource {
         - If the artifact source could not be created.
     */
     pub fn new_authenticated(auth: &HashMap<ArtifactProvider, String>) -> RokitResult<Self> {
-        let github = match auth.get(&ArtifactProvider::GitHub) {
-            Some(token) => GithubProvider::new_authenticated(token)?,
-            None => GithubProvider::new()?,
-        };
-        Ok(Self { github })
+        Self::new_with_options(auth, &ClientOptions::default())
+    }
+
+    /**
+        Creates a new artifact source, authenticated using any of the given
+        tokens, and with the given options for the underlying HTTP clients.
+
+        # Errors
+
+        - If the artifact source could not be created.
+    */
+    pub fn new_with_options(
+        auth: &HashMap<ArtifactProvider, String>,
+        options: &ClientOptions,
+    ) -> RokitResult<Self> {
+        let token = |provider| auth.get(&provider).map(String::as_str);
+        let github = GithubProvider::new_with_options(token(ArtifactProvider::GitHub), options)?;
+        let gitlab = GitlabProvider::new_with_options(token(ArtifactProvider::GitLab), options)?;
+        let bitbucket =
+            BitbucketProvider::new_with_options(token(ArtifactProvider::Bitbucket), options)?;
+        Ok(Self {
+            github,
+            gitlab,
+            bitbucket,
+        })
     }
 
     /**
@@ -60,7 +95,119 @@ impl ArtifactSource {
     pub async fn get_latest_release(&self, id: &ToolId) -> RokitResult<Release> {
         Ok(match id.provider() {
             ArtifactProvider::GitHub => self.github.get_latest_release(id).await?,
+            ArtifactProvider::GitLab => self.gitlab.get_latest_release(id).await?,
+            ArtifactProvider::Bitbucket => self.bitbucket.get_latest_release(id).await?,
+        })
+    }
+
+    /**
+        Gets the latest releases for many tools, with at most `max_in_flight`
+        requests running at once, to avoid hitting provider rate limits.
+
+        Results are returned for every tool, in the order they were given,
+        and a failure for one tool does not prevent fetching the others.
+
+        See `DEFAULT_MAX_IN_FLIGHT` for a reasonable default limit.
+    */
+    pub async fn get_latest_releases(
+        &self,
+        ids: impl IntoIterator<Item = ToolId>,
+        max_in_flight: usize,
+    ) -> Vec<(ToolId, RokitResult<Release>)> {
+        run_bounded(ids, max_in_flight, |id| async move {
+            self.get_latest_release(&id).await
         })
+        .await
+    }
+
+    /**
+        Lists all published versions for a tool, in descending order.
+
+        # Errors
+
+        - If the releases for the tool could not be fetched.
+    */
+    pub async fn list_versions(&self, id: &ToolId) -> RokitResult<Vec<Version>> {
+        Ok(match id.provider() {
+            ArtifactProvider::GitHub => self.github.list_versions(id).await?,
+            ArtifactProvider::GitLab => self.gitlab.list_versions(id).await?,
+            ArtifactProvider::Bitbucket => self.bitbucket.list_versions(id).await?,
+        })
+    }
+
+    /**
+        Resolves the latest published version for a tool, which excludes
+        any prerelease versions, such as `2.0.0-rc.1`, unless opted in.
+
+        Returns `None` if the tool has no matching published versions.
+
+        # Errors
+
+        - If the releases for the tool could not be fetched.
+    */
+    pub async fn resolve_latest_version(
+        &self,
+        id: &ToolId,
+        include_prereleases: bool,
+    ) -> RokitResult<Option<Version>> {
+        let versions = self.list_versions(id).await?;
+        Ok(versions
+            .into_iter()
+            .find(|version| include_prereleases || version.pre.is_empty()))
+    }
+
+    /**
+        Resolves a consolidated install plan for many tool requirements, such
+        as the ones from several manifests, where every unique tool id is
+        resolved once, to the highest version that satisfies all of its
+        requirements, with at most `max_in_flight` requests running at once.
+
+        Tool ids are compared case-insensitively, and tools that are only
+        required at a single exact version are resolved without any requests.
+
+        See `DEFAULT_MAX_IN_FLIGHT` for a reasonable default limit.
+
+        # Errors
+
+        - If the versions for a tool could not be fetched.
+        - If no published version satisfies one of the requirements for a tool.
+        - If no published version satisfies two of the requirements for a tool.
+    */
+    pub async fn resolve_install_plan(
+        &self,
+        requirements: impl IntoIterator<Item = impl Into<ToolVersionSpec>>,
+        max_in_flight: usize,
+    ) -> RokitResult<InstallPlan> {
+        let tools = group_requirements(requirements.into_iter().map(Into::into));
+
+        let mut exact_versions = Vec::with_capacity(tools.len());
+        for tool in &tools {
+            exact_versions.push(tool.exact_version()?);
+        }
+
+        let needs_versions = tools
+            .iter()
+            .zip(&exact_versions)
+            .filter(|(_, exact)| exact.is_none())
+            .map(|(tool, _)| tool.id.clone());
+        let mut available = run_bounded(needs_versions, max_in_flight, |id| async move {
+            self.list_versions(&id).await
+        })
+        .await
+        .into_iter();
+
+        let mut specs = Vec::with_capacity(tools.len());
+        for (tool, exact) in tools.iter().zip(exact_versions) {
+            let spec = if let Some(version) = exact {
+                (tool.id.clone(), version).into()
+            } else {
+                let (_, versions) = available.next().expect("versions were listed");
+                tool.resolve(&versions?)?
+            };
+            specs.push(spec);
+        }
+
+        Ok(InstallPlan { specs })
     }
 
     /**
@@ -73,6 +220,45 @@ impl ArtifactSource {
     pub async fn get_specific_release(&self, spec: &ToolSpec) -> RokitResult<Release> {
         Ok(match spec.provider() {
             ArtifactProvider::GitHub => self.github.get_specific_release(spec).await?,
+            ArtifactProvider::GitLab => self.gitlab.get_specific_release(spec).await?,
+            ArtifactProvider::Bitbucket => self.bitbucket.get_specific_release(spec).await?,
+        })
+    }
+
+    /**
+        Resolves what would be installed for a specific tool, by fetching its
+        release and selecting the artifact most compatible with the given system.
+
+        This never downloads or extracts the selected artifact.
+
+        # Errors
+
+        - If the specific release could not be fetched.
+        - If no artifact in the release is compatible with the given system.
+    */
+    pub async fn resolve_install(
+        &self,
+        spec: &ToolSpec,
+        system: Descriptor,
+    ) -> RokitResult<ResolvedInstall> {
+        let release = self.get_specific_release(spec).await?;
+        ResolvedInstall::from_release(&release, system)
+    }
+
+    /**
+        Gets a release for a tool by its literal tag name,
+        which does not need to be a valid semver version.
+
+        # Errors
+
+        - If no release with the given tag exists.
+        - If the release could not be fetched.
+    */
+    pub async fn get_release_by_tag(&self, id: &ToolId, tag: &str) -> RokitResult<Release> {
+        Ok(match id.provider() {
+            ArtifactProvider::GitHub => self.github.get_release_by_tag(id, tag).await?,
+            ArtifactProvider::GitLab => self.gitlab.get_release_by_tag(id, tag).await?,
+            ArtifactProvider::Bitbucket => self.bitbucket.get_release_by_tag(id, tag).await?,
         })
     }
 
@@ -84,8 +270,41 @@ impl ArtifactSource {
         - If the artifact contents could not be downloaded.
     */
     pub async fn download_artifact_contents(&self, artifact: &Artifact) -> RokitResult<Vec<u8>> {
+        self.download_artifact_contents_with_progress(artifact, |_, _| {})
+            .await
+    }
+
+    /**
+        Downloads the contents of an artifact, calling the given callback with
+        the number of bytes downloaded so far, and the total size, if known.
+
+        The total size is taken from the `Content-Length` header of the response.
+
+        # Errors
+
+        - If the artifact contents could not be downloaded.
+    */
+    pub async fn download_artifact_contents_with_progress(
+        &self,
+        artifact: &Artifact,
+        progress: impl FnMut(u64, Option<u64>) + Send,
+    ) -> RokitResult<Vec<u8>> {
         Ok(match &artifact.provider {
-            ArtifactProvider::GitHub => self.github.download_artifact_contents(artifact).await?,
+            ArtifactProvider::GitHub => {
+                self.github
+                    .download_artifact_contents_with_progress(artifact, progress)
+                    .await?
+            }
+            ArtifactProvider::GitLab => {
+                self.gitlab
+                    .download_artifact_contents_with_progress(artifact, progress)
+                    .await?
+            }
+            ArtifactProvider::Bitbucket => {
+                self.bitbucket
+                    .download_artifact_contents_with_progress(artifact, progress)
+                    .await?
+            }
         })
     }
 }
@@ -0,0 +1,448 @@
+use std::{
+    path::{Component, Path, PathBuf},
+    sync::Arc,
+};
+
+use tokio::fs::{create_dir_all, read, read_dir, remove_file, rename, write};
+use tracing::{debug, instrument, warn};
+
+use crate::{
+    result::{RokitError, RokitResult},
+    sources::{sha256_hex, Artifact, ArtifactSource, ExtractedBinary, Release},
+    tool::ToolSpec,
+    util::fs::path_exists,
+};
+
+/**
+    Cache for downloaded artifacts, which lets installs reuse previously downloaded
+    artifacts instead of fetching them again, and also work fully offline.
+
+    Artifacts are keyed by the canonical tool id, version, and the full
+    file name of the asset, including any archive extensions, so that
+    assets that only differ in their archive format never collide.
+
+    Can be cheaply cloned while still referring to the same underlying data.
+*/
+#[derive(Debug, Clone)]
+pub struct ArtifactCache {
+    cache_dir: Arc<Path>,
+    offline: bool,
+}
+
+impl ArtifactCache {
+    /**
+        Returns a copy of this `ArtifactCache` that either allows,
+        or does not allow, downloading artifacts that are not cached.
+
+        When offline, all artifacts must already be present in the
+        cache, and a `RokitError::NotCached` error is returned if not.
+    */
+    #[must_use]
+    pub fn with_offline(mut self, offline: bool) -> Self {
+        self.offline = offline;
+        self
+    }
+
+    /**
+        Checks if this `ArtifactCache` is offline, and does not allow
+        downloading artifacts that are not already present in the cache.
+    */
+    #[must_use]
+    pub fn is_offline(&self) -> bool {
+        self.offline
+    }
+
+    fn spec_dir(&self, spec: &ToolSpec) -> PathBuf {
+        // NOTE: The canonical id is case-insensitive and always includes the
+        // provider, hashing it gives us a safe and stable directory name
+        let key = format!("{}@{}", spec.id().canonical(), spec.version());
+        self.cache_dir.join(sha256_hex(key))
+    }
+
+    fn artifact_path(&self, artifact: &Artifact) -> Option<PathBuf> {
+        let name = artifact.file_name.as_deref()?;
+        let mut components = Path::new(name).components();
+        let is_plain_name = matches!(
+            (components.next(), components.next()),
+            (Some(Component::Normal(_)), None)
+        );
+        is_plain_name.then(|| self.spec_dir(&artifact.tool_spec).join(name))
+    }
+
+    /**
+        Gets the cached contents of the given artifact, if any.
+
+        # Errors
+
+        - If the cached contents could not be read.
+    */
+    pub async fn get(&self, artifact: &Artifact) -> RokitResult<Option<Vec<u8>>> {
+        let Some(path) = self.artifact_path(artifact) else {
+            return Ok(None);
+        };
+        if path_exists(&path).await {
+            Ok(Some(read(&path).await?))
+        } else {
+            Ok(None)
+        }
+    }
+
+    /**
+        Stores the contents of the given artifact in the cache.
+
+        Artifacts without a file name, or with a file name that
+        is not a plain file name, can not be cached and are skipped.
+
+        # Errors
+
+        - If the contents could not be written to the cache.
+    */
+    pub async fn insert(&self, artifact: &Artifact, contents: &[u8]) -> RokitResult<()> {
+        let Some(path) = self.artifact_path(artifact) else {
+            return Ok(());
+        };
+        let dir = path.parent().expect("artifact paths have a parent");
+        create_dir_all(dir).await?;
+        // NOTE: Write to a temporary file first, to never leave partially
+        // written artifacts in the cache if we are interrupted while writing
+        let mut temp_path = path.clone().into_os_string();
+        temp_path.push(".partial");
+        write(&temp_path, contents).await?;
+        rename(&temp_path, &path).await?;
+        Ok(())
+    }
+
+    /**
+        Removes the cached contents of the given artifact, if any.
+
+        # Errors
+
+        - If the cached contents could not be removed.
+    */
+    pub async fn remove(&self, artifact: &Artifact) -> RokitResult<()> {
+        let Some(path) = self.artifact_path(artifact) else {
+            return Ok(());
+        };
+        if path_exists(&path).await {
+            remove_file(&path).await?;
+        }
+        Ok(())
+    }
+
+    /**
+        Resolves a release for the given tool specification using only the
+        cache, containing all of the artifacts that were previously cached.
+
+        Cached artifacts have no download URL, and keep the original file
+        name and format of the asset that they were downloaded from.
+
+        # Errors
+
+        - If no artifacts are cached for the given tool specification.
+        - If the cache directory could not be read.
+    */
+    #[instrument(skip(self), fields(%spec), level = "debug")]
+    pub async fn get_cached_release(&self, spec: &ToolSpec) -> RokitResult<Release> {
+        let dir = self.spec_dir(spec);
+
+        let mut artifacts = Vec::new();
+        if path_exists(&dir).await {
+            let mut entries = read_dir(&dir).await?;
+            while let Some(entry) = entries.next_entry().await? {
+                let path = entry.path();
+                let Some(name) = path.file_name().and_then(|name| name.to_str()) else {
+                    continue;
+                };
+                if !entry.file_type().await?.is_file() || name.ends_with(".partial") {
+                    continue;
+                }
+                artifacts.push(Artifact::from_cached_file_name(name, spec));
+            }
+        }
+
+        if artifacts.is_empty() {
+            return Err(RokitError::NotCached {
+                id: spec.id().clone().into(),
+                version: spec.version().clone(),
+            });
+        }
+
+        artifacts.sort_by(|a, b| a.file_name.cmp(&b.file_name));
+        Ok(Release {
+            changelog: None,
+            artifacts,
+        })
+    }
+
+    /**
+        Gets the contents of the given artifact from the cache, or
+        downloads and caches them if they have not been cached yet.
+
+        # Errors
+
+        - If this cache is offline, and the artifact is not cached.
+        - If the artifact contents could not be downloaded.
+        - If the cache could not be read or written.
+    */
+    #[instrument(skip(self, source, artifact), level = "debug")]
+    pub async fn download_artifact_contents(
+        &self,
+        source: &ArtifactSource,
+        artifact: &Artifact,
+    ) -> RokitResult<Vec<u8>> {
+        if let Some(contents) = self.get(artifact).await? {
+            debug!(name = ?artifact.name, "using cached artifact");
+            return Ok(contents);
+        }
+
+        if self.offline {
+            return Err(RokitError::NotCached {
+                id: artifact.tool_spec.id().clone().into(),
+                version: artifact.tool_spec.version().clone(),
+            });
+        }
+
+        let contents = source.download_artifact_contents(artifact).await?;
+        self.insert(artifact, &contents).await?;
+        Ok(contents)
+    }
+
+    /**
+        Gets the contents of the given artifact from the cache, or downloads
+        them, and extracts them - see [`Artifact::extract_contents`].
+
+        If the contents turn out to be a corrupt archive, such as a truncated
+        download, they are discarded and downloaded again exactly once before
+        giving up. Offline caches never download the artifact again.
+
+        # Errors
+
+        - If this cache is offline, and the artifact is not cached.
+        - If the artifact contents could not be downloaded.
+        - If the cache could not be read or written.
+        - If the contents could not be extracted, even after downloading again.
+    */
+    #[instrument(skip(self, source, artifact), level = "debug")]
+    pub async fn download_and_extract_contents(
+        &self,
+        source: &ArtifactSource,
+        artifact: &Artifact,
+    ) -> RokitResult<ExtractedBinary> {
+        let contents = self.download_artifact_contents(source, artifact).await?;
+        match artifact.extract_contents(contents).await {
+            Err(err) if err.is_corrupt_archive() && !self.offline => {
+                warn!(name = ?artifact.name, "artifact is corrupt, downloading again: {err}");
+                self.remove(artifact).await?;
+                let contents = self.download_artifact_contents(source, artifact).await?;
+                artifact.extract_contents(contents).await
+            }
+            res => res,
+        }
+    }
+
+    pub(crate) async fn load(home_path: impl AsRef<Path>) -> RokitResult<Self> {
+        let cache_dir: Arc<Path> = home_path.as_ref().join("artifact-cache").into();
+        create_dir_all(&cache_dir).await?;
+        Ok(Self {
+            cache_dir,
+            offline: false,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        collections::HashMap,
+        env::consts::EXE_SUFFIX,
+        io::{Cursor, Write},
+    };
+
+    use wiremock::{
+        matchers::{method, path},
+        Mock, MockServer, ResponseTemplate,
+    };
+    use zip::{write::SimpleFileOptions, ZipWriter};
+
+    use crate::sources::{
+        github::models::GithubAsset, test_options, ArtifactFormat, ArtifactProvider,
+    };
+
+    use super::*;
+
+    const BINARY_CONTENTS: &[u8] = b"#!/bin/sh\necho tool\n";
+
+    fn new_artifact(spec: &str, file_name: &str) -> Artifact {
+        let asset = GithubAsset {
+            id: 1,
+            url: "https://api.github.com/repos/author/tool/releases/assets/1"
+                .parse()
+                .unwrap(),
+            name: file_name.to_string(),
+        };
+        Artifact::from_github_release_asset(&asset, &spec.parse().unwrap())
+    }
+
+    #[tokio::test]
+    async fn cache_hit() {
+        let home = tempfile::tempdir().unwrap();
+        let cache = ArtifactCache::load(home.path()).await.unwrap();
+        let artifact = new_artifact("author/tool@1.0.0", "tool-linux-x86_64.zip");
+        cache.insert(&artifact, b"contents").await.unwrap();
+
+        // Tool ids are case-insensitive, and should share cached artifacts
+        let other = new_artifact("Author/Tool@1.0.0", "tool-linux-x86_64.zip");
+        assert_eq!(cache.get(&other).await.unwrap().unwrap(), b"contents");
+
+        // Downloading should never reach the source when cached
+        let source = ArtifactSource::new().unwrap();
+        let offline = cache.clone().with_offline(true);
+        let contents = offline.download_artifact_contents(&source, &artifact);
+        assert_eq!(contents.await.unwrap(), b"contents");
+
+        // Cached releases should keep the original names and formats
+        let release = offline.get_cached_release(&artifact.tool_spec).await;
+        let cached = release.unwrap().artifacts;
+        assert_eq!(cached.len(), 1);
+        assert_eq!(cached[0].name.as_deref(), Some("tool-linux-x86_64"));
+        assert_eq!(
+            cached[0].file_name.as_deref(),
+            Some("tool-linux-x86_64.zip")
+        );
+        assert_eq!(cached[0].format, Some(ArtifactFormat::Zip));
+        assert_eq!(cached[0].url, None);
+    }
+
+    #[tokio::test]
+    async fn cache_miss() {
+        let home = tempfile::tempdir().unwrap();
+        let cache = ArtifactCache::load(home.path()).await.unwrap();
+        let artifact = new_artifact("author/tool@1.0.0", "tool-linux-x86_64.zip");
+        cache.insert(&artifact, b"1").await.unwrap();
+        // Different versions, asset names, and formats are different artifacts
+        for artifact in [
+            new_artifact("author/tool@2.0.0", "tool-linux-x86_64.zip"),
+            new_artifact("author/tool@1.0.0", "tool-macos-x86_64.zip"),
+            new_artifact("author/tool@1.0.0", "tool-linux-x86_64.tar.gz"),
+            new_artifact("other/tool@1.0.0", "tool-linux-x86_64.zip"),
+        ] {
+            assert_eq!(cache.get(&artifact).await.unwrap(), None);
+        }
+        // Names that are not plain file names are never cached
+        let unsafe_artifact = new_artifact("author/tool@1.0.0", "../tool-linux.zip");
+        cache.insert(&unsafe_artifact, b"2").await.unwrap();
+        assert_eq!(cache.get(&unsafe_artifact).await.unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn offline_miss() {
+        let home = tempfile::tempdir().unwrap();
+        let cache = ArtifactCache::load(home.path())
+            .await
+            .unwrap()
+            .with_offline(true);
+        let artifact = new_artifact("author/tool@1.0.0", "tool-linux-x86_64.zip");
+
+        let source = ArtifactSource::new().unwrap();
+        let err = cache
+            .download_artifact_contents(&source, &artifact)
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            RokitError::NotCached { id, version }
+                if *id == *artifact.tool_spec.id() && version == *artifact.tool_spec.version()
+        ));
+
+        let err = cache.get_cached_release(&artifact.tool_spec).await;
+        assert!(matches!(err, Err(RokitError::NotCached { .. })));
+    }
+
+    fn build_zip(file_name: &str) -> Vec<u8> {
+        let mut writer = ZipWriter::new(Cursor::new(Vec::new()));
+        writer
+            .start_file(file_name, SimpleFileOptions::default())
+            .unwrap();
+        writer.write_all(BINARY_CONTENTS).unwrap();
+        writer.finish().unwrap().into_inner()
+    }
+
+    async fn mock_downloads(responses: Vec<Vec<u8>>) -> (MockServer, ArtifactSource, Artifact) {
+        let server = MockServer::start().await;
+        let num_responses = responses.len();
+        for (index, body) in responses.into_iter().enumerate() {
+            let mock = Mock::given(method("GET"))
+                .and(path("/repos/author/tool/releases/assets/1"))
+                .respond_with(ResponseTemplate::new(200).set_body_bytes(body));
+            // NOTE: Earlier responses take priority, and are only used once,
+            // while the last response is used for any remaining requests
+            let mock = if index + 1 < num_responses {
+                mock.with_priority(1).up_to_n_times(1)
+            } else {
+                mock
+            };
+            mock.mount(&server).await;
+        }
+
+        let options = test_options(ArtifactProvider::GitHub, server.uri());
+        let source = ArtifactSource::new_with_options(&HashMap::new(), &options).unwrap();
+
+        let artifact = Artifact {
+            url: Some(
+                format!("{}/repos/author/tool/releases/assets/1", server.uri())
+                    .parse()
+                    .unwrap(),
+            ),
+            ..new_artifact("author/tool@1.0.0", "tool-linux-x86_64.zip")
+        };
+
+        (server, source, artifact)
+    }
+
+    #[tokio::test]
+    async fn corrupt_archive_downloaded_again() {
+        let valid = build_zip(&format!("tool{EXE_SUFFIX}"));
+        let truncated = valid[..valid.len() / 2].to_vec();
+        let (server, source, artifact) = mock_downloads(vec![truncated, valid.clone()]).await;
+
+        let home = tempfile::tempdir().unwrap();
+        let cache = ArtifactCache::load(home.path()).await.unwrap();
+        let extracted = cache.download_and_extract_contents(&source, &artifact);
+        assert_eq!(extracted.await.unwrap().contents, BINARY_CONTENTS);
+
+        // The corrupt download should have been replaced in the cache
+        assert_eq!(server.received_requests().await.unwrap().len(), 2);
+        assert_eq!(cache.get(&artifact).await.unwrap().unwrap(), valid);
+    }
+
+    #[tokio::test]
+    async fn corrupt_archive_downloaded_again_once() {
+        let valid = build_zip(&format!("tool{EXE_SUFFIX}"));
+        let truncated = valid[..valid.len() / 2].to_vec();
+        let (server, source, artifact) = mock_downloads(vec![truncated]).await;
+
+        let home = tempfile::tempdir().unwrap();
+        let cache = ArtifactCache::load(home.path()).await.unwrap();
+        let err = cache
+            .download_and_extract_contents(&source, &artifact)
+            .await
+            .unwrap_err();
+        assert!(err.is_corrupt_archive(), "{err:?}");
+        assert_eq!(server.received_requests().await.unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn valid_archive_not_downloaded_again() {
+        // Errors that are not caused by a corrupt archive should never retry
+        let (server, source, artifact) = mock_downloads(vec![build_zip("other")]).await;
+
+        let home = tempfile::tempdir().unwrap();
+        let cache = ArtifactCache::load(home.path()).await.unwrap();
+        let err = cache
+            .download_and_extract_contents(&source, &artifact)
+            .await
+            .unwrap_err();
+        assert!(!err.is_corrupt_archive(), "{err:?}");
+        assert_eq!(server.received_requests().await.unwrap().len(), 1);
+    }
+}
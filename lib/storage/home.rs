@@ -6,9 +6,9 @@ use tokio::fs::create_dir_all;
 
 use crate::manifests::AuthManifest;
 use crate::result::{RokitError, RokitResult};
-use crate::sources::ArtifactSource;
+use crate::sources::{ArtifactProvider, ArtifactSource, ClientOptions};
 
-use super::{ToolCache, ToolStorage};
+use super::{ArtifactCache, ToolCache, ToolStorage};
 
 /**
     Rokit's home directory - this is where Rokit stores its
@@ -23,6 +23,7 @@ pub struct Home {
     path: Arc<Path>,
     tool_storage: ToolStorage,
     tool_cache: ToolCache,
+    artifact_cache: ArtifactCache,
 }
 
 impl Home {
@@ -32,13 +33,17 @@ impl Home {
     async fn load_from_path(path: impl Into<PathBuf>) -> RokitResult<Self> {
         let path: Arc<Path> = path.into().into();
 
-        let (tool_storage, tool_cache) =
-            tokio::try_join!(ToolStorage::load(&path), ToolCache::load(&path))?;
+        let (tool_storage, tool_cache, artifact_cache) = tokio::try_join!(
+            ToolStorage::load(&path),
+            ToolCache::load(&path),
+            ArtifactCache::load(&path),
+        )?;
 
         Ok(Self {
             path,
             tool_storage,
             tool_cache,
+            artifact_cache,
         })
     }
 
@@ -91,12 +96,26 @@ impl Home {
         &self.tool_cache
     }
 
+    /**
+        Returns a reference to the `ArtifactCache` for this `Home`.
+    */
+    #[must_use]
+    pub fn artifact_cache(&self) -> &ArtifactCache {
+        &self.artifact_cache
+    }
+
     /**
         Creates a new `ArtifactSource` for this `Home`.
 
         This will load any stored authentication from disk and use
         it to authenticate with the artifact source and various providers.
 
+        If no GitHub token is stored, the `GITHUB_TOKEN` environment
+        variable will be used instead, if it is set and non-empty.
+
+        Similarly, the `GITHUB_API_URL` environment variable may be set to
+        use a self-hosted GitHub Enterprise instance instead of github.com.
+
         # Errors
 
         - If the auth manifest could not be loaded or created.
@@ -104,7 +123,21 @@ impl Home {
     */
     pub async fn artifact_source(&self) -> RokitResult<ArtifactSource> {
         let auth = AuthManifest::load_or_create(&self.path).await?;
-        ArtifactSource::new_authenticated(&auth.get_all_tokens())
+        let mut tokens = auth.get_all_tokens();
+        if let Ok(token) = var("GITHUB_TOKEN") {
+            if !token.trim().is_empty() {
+                tokens.entry(ArtifactProvider::GitHub).or_insert(token);
+            }
+        }
+        let mut options = ClientOptions::default();
+        if let Ok(base_url) = var("GITHUB_API_URL") {
+            if !base_url.trim().is_empty() {
+                options
+                    .base_urls
+                    .insert(ArtifactProvider::GitHub, base_url.trim().to_string());
+            }
+        }
+        ArtifactSource::new_with_options(&tokens, &options)
     }
 
     /**
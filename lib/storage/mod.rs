@@ -1,8 +1,10 @@
+mod artifact_cache;
 mod home;
 mod metadata;
 mod tool_cache;
 mod tool_storage;
 
+pub use self::artifact_cache::ArtifactCache;
 pub use self::home::Home;
 pub use self::tool_cache::ToolCache;
 pub use self::tool_storage::ToolStorage;
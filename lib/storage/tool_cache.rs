@@ -14,21 +14,37 @@ use dashmap::DashSet;
 use semver::Version;
 use serde::Deserialize;
 use tokio::{fs::create_dir_all, task::spawn_blocking, time::Instant};
-use tracing::{instrument, trace};
+use toml_edit::{value, Array, DocumentMut};
+use tracing::{instrument, trace, warn};
 
 use crate::{
-    result::RokitResult,
+    result::{RokitError, RokitResult},
     tool::{ToolId, ToolSpec},
+    util::fs::{load_from_file, save_to_file},
 };
 
+const TRUST_FILE_NAME: &str = "trusted.toml";
+const TRUST_FILE_HEADER: &str = "\
+# This file lists tools that have been trusted by Rokit, a toolchain manager for Roblox projects.
+# Tools that are not listed here will ask for trust before they are installed.
+
+";
+
 /**
     Cache for trusted tool identifiers and installed tool specifications.
 
+    Trusted tools are stored in a `trusted.toml` file in the Rokit home
+    directory, and installed tools in `tool-storage/cache.json`.
+
     Can be cheaply cloned while still referring to the same underlying data.
 */
 #[derive(Debug, Default, Clone, Deserialize)]
 pub struct ToolCache {
+    // NOTE: Older versions of Rokit stored trusted tools in the
+    // json cache file, which are still read here for migration
+    #[serde(default)]
     trusted: Arc<DashSet<ToolId>>,
+    #[serde(default)]
     installed: Arc<DashSet<ToolSpec>>,
     #[serde(default, skip)]
     needs_saving: Arc<AtomicBool>,
@@ -49,9 +65,9 @@ impl ToolCache {
         Returns `true` if the tool was added and not already trusted.
     */
     #[must_use]
-    pub fn add_trust(&self, tool: ToolId) -> bool {
+    pub fn add_trust(&self, tool: &ToolId) -> bool {
         self.needs_saving.store(true, Ordering::SeqCst);
-        self.trusted.insert(tool)
+        self.trusted.insert(tool.clone())
     }
 
     /**
@@ -166,12 +182,36 @@ impl ToolCache {
         home_path.as_ref().join("tool-storage").join("cache.json")
     }
 
+    fn trust_path(home_path: impl AsRef<Path>) -> PathBuf {
+        home_path.as_ref().join(TRUST_FILE_NAME)
+    }
+
     #[instrument(skip(home_path), level = "trace")]
     pub(crate) async fn load(home_path: impl AsRef<Path>) -> RokitResult<Self> {
         let start = Instant::now();
-        let path = Self::path(home_path);
+        let path = Self::path(&home_path);
         let this = load_impl(path.clone()).await?;
         trace!(?path, elapsed = ?start.elapsed(), "Loading tool cache");
+
+        let trust_path = Self::trust_path(&home_path);
+        match load_from_file::<_, DocumentMut, _>(&trust_path).await {
+            Ok(document) => {
+                this.trusted.clear();
+                for tool in trusted_from_document(&document) {
+                    this.trusted.insert(tool);
+                }
+            }
+            Err(RokitError::FileNotFound(_)) => {
+                // NOTE: Make sure that any tools trusted in the json
+                // cache file are migrated to the trust file on save
+                if !this.trusted.is_empty() {
+                    this.needs_saving.store(true, Ordering::SeqCst);
+                }
+            }
+            Err(e) => return Err(e),
+        }
+        trace!(path = ?trust_path, elapsed = ?start.elapsed(), "Loading trusted tools");
+
         Ok(this)
     }
 
@@ -179,9 +219,18 @@ impl ToolCache {
     pub(crate) async fn save(&self, home_path: impl AsRef<Path>) -> RokitResult<()> {
         self.needs_saving.store(false, Ordering::SeqCst);
         let start = Instant::now();
-        let path = Self::path(home_path);
+        let path = Self::path(&home_path);
         save_impl(path.clone(), self).await?;
         trace!(?path, elapsed = ?start.elapsed(), "Saved tool cache");
+
+        let trust_path = Self::trust_path(&home_path);
+        let contents = format!(
+            "{TRUST_FILE_HEADER}{}",
+            trusted_to_document(&self.all_trusted())
+        );
+        save_to_file(&trust_path, contents).await?;
+        trace!(path = ?trust_path, elapsed = ?start.elapsed(), "Saved trusted tools");
+
         Ok(())
     }
 
@@ -228,8 +277,8 @@ async fn save_impl(path: PathBuf, cache: &ToolCache) -> RokitResult<()> {
     // NOTE: We save using sorted json arrays here, which is
     // compatible with the deserialize implementation for DashSet,
     // while also being easier to read for any human inspectors.
+    // Trusted tools are saved separately, in the trust file.
     let json = serde_json::json!({
-        "trusted": cache.all_trusted(),
         "installed": cache.all_installed(),
     });
 
@@ -248,3 +297,160 @@ async fn save_impl(path: PathBuf, cache: &ToolCache) -> RokitResult<()> {
     result.await??;
     Ok(())
 }
+
+fn trusted_from_document(document: &DocumentMut) -> Vec<ToolId> {
+    let Some(trusted) = document.get("trusted") else {
+        return Vec::new();
+    };
+    let Some(array) = trusted.as_array() else {
+        warn!(
+            "Encountered invalid list of trusted tools in {TRUST_FILE_NAME}!\
+            \nExpected: Array\
+            \nActual: {}",
+            trusted.type_name()
+        );
+        return Vec::new();
+    };
+    array
+        .iter()
+        .filter_map(|item| match item.as_str().map(str::parse::<ToolId>) {
+            Some(Ok(tool)) => Some(tool),
+            Some(Err(e)) => {
+                warn!(
+                    "A trusted tool could not be parsed!\
+                    \nThe tool will not be trusted.\
+                    \nError: {e}"
+                );
+                None
+            }
+            None => {
+                warn!(
+                    "Encountered invalid trusted tool in {TRUST_FILE_NAME}!\
+                    \nExpected: String\
+                    \nActual: {}",
+                    item.type_name()
+                );
+                None
+            }
+        })
+        .collect()
+}
+
+fn trusted_to_document(trusted: &[ToolId]) -> DocumentMut {
+    // NOTE: Each tool is placed on its own line, which
+    // is easier to read and edit than a single long line
+    let mut array = trusted.iter().map(ToString::to_string).collect::<Array>();
+    if !array.is_empty() {
+        for item in array.iter_mut() {
+            item.decor_mut().set_prefix("\n    ");
+        }
+        array.set_trailing("\n");
+        array.set_trailing_comma(true);
+    }
+    let mut document = DocumentMut::new();
+    document.insert("trusted", value(array));
+    document
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn new_id(id: &str) -> ToolId {
+        id.parse().unwrap()
+    }
+
+    #[test]
+    fn untrusted_until_added() {
+        let cache = ToolCache::new();
+        let id = new_id("author/tool");
+        assert!(!cache.is_trusted(&id));
+        assert!(cache.add_trust(&id));
+        assert!(!cache.add_trust(&id));
+        // Trust is keyed by the case-insensitive author and name
+        assert!(cache.is_trusted(&new_id("Author/Tool")));
+        assert!(!cache.is_trusted(&new_id("author/other-tool")));
+        assert!(cache.remove_trust(&id));
+        assert!(!cache.is_trusted(&id));
+    }
+
+    #[tokio::test]
+    async fn trust_round_trip() {
+        let home = tempfile::tempdir().unwrap();
+        let cache = ToolCache::load(home.path()).await.unwrap();
+        assert!(cache.all_trusted().is_empty());
+
+        let _ = cache.add_trust(&new_id("author/tool"));
+        let _ = cache.add_trust(&new_id("gitlab:author/other"));
+        assert!(cache.needs_saving());
+        cache.save(home.path()).await.unwrap();
+        assert!(!cache.needs_saving());
+
+        let loaded = ToolCache::load(home.path()).await.unwrap();
+        assert_eq!(loaded.all_trusted(), cache.all_trusted());
+        assert!(loaded.is_trusted(&new_id("author/tool")));
+        assert!(!loaded.is_trusted(&new_id("author/other")));
+
+        let _ = loaded.remove_trust(&new_id("author/tool"));
+        loaded.save(home.path()).await.unwrap();
+        let loaded = ToolCache::load(home.path()).await.unwrap();
+        assert_eq!(loaded.all_trusted(), [new_id("gitlab:author/other")]);
+    }
+
+    #[tokio::test]
+    async fn trust_saved_as_toml() {
+        let home = tempfile::tempdir().unwrap();
+        let cache = ToolCache::load(home.path()).await.unwrap();
+        let _ = cache.add_trust(&new_id("author/tool"));
+        let _ = cache.add_trust(&new_id("gitlab:author/other"));
+        cache.save(home.path()).await.unwrap();
+
+        let contents = std::fs::read_to_string(home.path().join(TRUST_FILE_NAME)).unwrap();
+        assert!(contents.starts_with(TRUST_FILE_HEADER));
+        let document = contents.parse::<DocumentMut>().unwrap();
+        assert_eq!(trusted_from_document(&document), cache.all_trusted());
+
+        // Trusted tools are no longer stored in the json cache file
+        let json = std::fs::read_to_string(ToolCache::path(home.path())).unwrap();
+        assert!(!json.contains("trusted"));
+    }
+
+    #[tokio::test]
+    async fn trust_migrated_from_json() {
+        let home = tempfile::tempdir().unwrap();
+        let path = ToolCache::path(home.path());
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(
+            &path,
+            r#"{"trusted":["author/tool"],"installed":["author/tool@1.0.0"]}"#,
+        )
+        .unwrap();
+
+        let cache = ToolCache::load(home.path()).await.unwrap();
+        assert!(cache.is_trusted(&new_id("author/tool")));
+        assert!(cache.needs_saving());
+        cache.save(home.path()).await.unwrap();
+
+        let loaded = ToolCache::load(home.path()).await.unwrap();
+        assert!(!loaded.needs_saving());
+        assert_eq!(loaded.all_trusted(), [new_id("author/tool")]);
+        assert_eq!(
+            loaded.all_installed(),
+            ["author/tool@1.0.0".parse::<ToolSpec>().unwrap()]
+        );
+
+        // The trust file takes precedence over the json cache file, once it exists
+        let _ = loaded.remove_trust(&new_id("author/tool"));
+        loaded.save(home.path()).await.unwrap();
+        let loaded = ToolCache::load(home.path()).await.unwrap();
+        assert!(loaded.all_trusted().is_empty());
+    }
+
+    #[test]
+    fn trust_skips_invalid_entries() {
+        let document =
+            r#"trusted = ["author/tool", 1, "not a tool id"]"#.parse::<DocumentMut>().unwrap();
+        assert_eq!(trusted_from_document(&document), [new_id("author/tool")]);
+        assert!(trusted_from_document(&DocumentMut::new()).is_empty());
+    }
+}
@@ -1,24 +1,25 @@
 use std::{
     env::consts::{EXE_EXTENSION, EXE_SUFFIX},
-    path::{Path, PathBuf},
+    path::{Component, Path, PathBuf},
     sync::Arc,
 };
 
 use filepath::FilePath;
 use futures::{stream::FuturesUnordered, TryStreamExt};
 use tokio::{
-    fs::{create_dir_all, read, read_dir, remove_file, rename},
+    fs::{create_dir_all, read, read_dir, remove_file, rename, write},
     sync::Mutex as AsyncMutex,
 };
-use tracing::{debug, trace};
+use tracing::{debug, trace, warn};
 
 use crate::{
     manifests::{AuthManifest, RokitManifest},
-    result::RokitResult,
+    result::{RokitError, RokitResult},
+    sources::{ExtractedBinary, ExtractedFile},
     storage::metadata::RokitLinkMetadata,
     system::current_exe_contents,
     tool::{ToolAlias, ToolSpec},
-    util::fs::{path_exists, write_executable_file},
+    util::fs::{path_exists, write_executable_file, write_executable_file_with_mode},
 };
 
 /**
@@ -82,6 +83,9 @@ impl ToolStorage {
     /**
         Replaces the binary contents for the given tool.
 
+        The file mode from the archive is kept, if the binary has
+        one, but the binary is always made executable on unix.
+
         # Errors
 
         - If the binary could not be written.
@@ -89,11 +93,77 @@ impl ToolStorage {
     pub async fn replace_tool_contents(
         &self,
         spec: &ToolSpec,
-        contents: impl AsRef<[u8]>,
+        binary: &ExtractedBinary,
     ) -> RokitResult<()> {
         let (dir_path, file_path) = self.tool_paths(spec);
         create_dir_all(dir_path).await?;
-        write_executable_file(&file_path, contents).await?;
+        write_executable_file_with_mode(&file_path, &binary.contents, binary.mode).await?;
+        Ok(())
+    }
+
+    /**
+        Writes companion files for the given tool, such as shell completions
+        or shared libraries, next to its binary, preserving their relative paths.
+
+        Symlinks are recreated as symlinks on Unix, and on other
+        platforms, the file they are pointing to is copied instead.
+
+        Files that would replace the binary of the tool itself are skipped,
+        since the binary has already been validated and stored separately.
+
+        # Errors
+
+        - If any of the paths are not relative, or traverse upwards.
+        - If any of the symlinks point outside of the tool directory.
+        - If any of the files could not be written.
+    */
+    pub async fn write_tool_files(
+        &self,
+        spec: &ToolSpec,
+        files: &[(PathBuf, ExtractedFile)],
+    ) -> RokitResult<()> {
+        let (dir_path, tool_path) = self.tool_paths(spec);
+        let tool_file_name = tool_path.file_name().expect("tools have file names");
+
+        // NOTE: Paths should already be sanitized during extraction,
+        // but we make sure to never write outside of the tool directory
+        for (relative_path, file) in files {
+            let is_safe = match file {
+                ExtractedFile::Contents(_) => is_contained_path(relative_path),
+                ExtractedFile::Symlink(target) => is_contained_link(relative_path, target),
+            };
+            if !is_safe {
+                return Err(RokitError::UnsafeArchivePath(
+                    relative_path.display().to_string(),
+                ));
+            }
+        }
+
+        // Write all regular files first, so that symlinks can be copied
+        let (links, contents): (Vec<_>, Vec<_>) = files
+            .iter()
+            .partition(|(_, file)| matches!(file, ExtractedFile::Symlink(_)));
+        for (relative_path, file) in contents.into_iter().chain(links) {
+            // NOTE: Tool paths are case-insensitive, so names
+            // differing only in casing may still be the same file
+            let is_tool_binary = relative_path
+                .to_str()
+                .zip(tool_file_name.to_str())
+                .is_some_and(|(path, name)| path.eq_ignore_ascii_case(name));
+            if is_tool_binary {
+                warn!(path = ?relative_path, "skipping file that would replace the tool binary");
+                continue;
+            }
+            let file_path = dir_path.join(relative_path);
+            if let Some(parent) = file_path.parent() {
+                create_dir_all(parent).await?;
+            }
+            match file {
+                ExtractedFile::Contents(contents) => write(&file_path, contents).await?,
+                ExtractedFile::Symlink(target) => write_symlink(&file_path, target).await?,
+            }
+        }
+
         Ok(())
     }
 
@@ -277,6 +347,46 @@ impl ToolStorage {
     }
 }
 
+// Utility functions for writing companion files
+
+fn is_contained_path(relative_path: &Path) -> bool {
+    relative_path
+        .components()
+        .all(|c| matches!(c, Component::Normal(_)))
+}
+
+fn is_contained_link(relative_path: &Path, target: &Path) -> bool {
+    if !is_contained_path(relative_path) {
+        return false;
+    }
+    let mut depth = relative_path.components().count() - 1;
+    for component in target.components() {
+        match component {
+            Component::Normal(_) => depth += 1,
+            Component::CurDir => {}
+            Component::ParentDir if depth > 0 => depth -= 1,
+            _ => return false,
+        }
+    }
+    true
+}
+
+#[cfg(unix)]
+async fn write_symlink(link_path: &Path, target: &Path) -> RokitResult<()> {
+    if path_exists(link_path).await {
+        remove_file(link_path).await?;
+    }
+    tokio::fs::symlink(target, link_path).await?;
+    Ok(())
+}
+
+#[cfg(not(unix))]
+async fn write_symlink(link_path: &Path, target: &Path) -> RokitResult<()> {
+    let target_path = link_path.parent().unwrap_or(link_path).join(target);
+    tokio::fs::copy(target_path, link_path).await?;
+    Ok(())
+}
+
 // Utility functions for migrating missing exe extensions from old Rokit versions
 
 fn should_check_exe_extensions() -> bool {
@@ -331,3 +441,81 @@ async fn skip_or_write_link_with_meta(
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn contained_links() {
+        let link = Path::new("bin/tool");
+        assert!(is_contained_link(link, Path::new("tool-1.2.3")));
+        assert!(is_contained_link(link, Path::new("../lib/libtool.so")));
+        assert!(!is_contained_link(link, Path::new("../../tool")));
+        assert!(!is_contained_link(link, Path::new("/usr/bin/tool")));
+        assert!(!is_contained_link(Path::new("tool"), Path::new("../tool")));
+        assert!(!is_contained_link(Path::new("../tool"), Path::new("tool")));
+    }
+
+    #[tokio::test]
+    async fn write_tool_files_skips_binary() {
+        let home = tempfile::tempdir().unwrap();
+        let storage = ToolStorage::load(home.path()).await.unwrap();
+        let spec = "author/tool@1.2.3".parse::<ToolSpec>().unwrap();
+        let binary = ExtractedBinary {
+            contents: b"binary contents".to_vec(),
+            mode: None,
+        };
+        storage.replace_tool_contents(&spec, &binary).await.unwrap();
+
+        // Patterns such as `*` may also match the binary, which must be kept intact
+        let files = [
+            (
+                PathBuf::from(format!("Tool{EXE_SUFFIX}")),
+                ExtractedFile::Contents(b"other contents".to_vec()),
+            ),
+            (
+                PathBuf::from("LICENSE"),
+                ExtractedFile::Contents(b"license".to_vec()),
+            ),
+        ];
+        storage.write_tool_files(&spec, &files).await.unwrap();
+
+        let (dir_path, tool_path) = storage.tool_paths(&spec);
+        assert_eq!(read(&tool_path).await.unwrap(), b"binary contents");
+        assert_eq!(read(dir_path.join("LICENSE")).await.unwrap(), b"license");
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn write_tool_files_with_symlink() {
+        let home = tempfile::tempdir().unwrap();
+        let storage = ToolStorage::load(home.path()).await.unwrap();
+        let spec = "author/tool@1.2.3".parse::<ToolSpec>().unwrap();
+        let files = [
+            (
+                PathBuf::from("bin/tool"),
+                ExtractedFile::Symlink(PathBuf::from("tool-1.2.3")),
+            ),
+            (
+                PathBuf::from("bin/tool-1.2.3"),
+                ExtractedFile::Contents(b"binary contents".to_vec()),
+            ),
+        ];
+        storage.write_tool_files(&spec, &files).await.unwrap();
+
+        let (dir_path, _) = storage.tool_paths(&spec);
+        let link_path = dir_path.join("bin").join("tool");
+        let target = tokio::fs::read_link(&link_path).await.unwrap();
+        assert_eq!(target, PathBuf::from("tool-1.2.3"));
+        assert_eq!(read(&link_path).await.unwrap(), b"binary contents");
+
+        // Links pointing outside of the tool directory should be refused
+        let evil = [(
+            PathBuf::from("bin/evil"),
+            ExtractedFile::Symlink(PathBuf::from("../../../../evil")),
+        )];
+        let result = storage.write_tool_files(&spec, &evil).await;
+        assert!(matches!(result, Err(RokitError::UnsafeArchivePath(_))));
+    }
+}
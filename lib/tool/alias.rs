@@ -18,8 +18,55 @@ pub enum ToolAliasParseError {
     Invalid(String),
     #[error("alias '{0}' contains whitespace")]
     ContainsWhitespace(String),
+    #[error("alias '{0}' contains a path separator")]
+    ContainsPathSeparator(String),
+    #[error("alias '{0}' is a reserved name")]
+    Reserved(String),
 }
 
+/**
+    Names that can not be used as aliases, since they would either conflict
+    with Rokit itself, be confusing next to its subcommands, or are reserved
+    as file names on Windows, and could not be used for a tool executable.
+*/
+const RESERVED_NAMES: &[&str] = &[
+    "rokit",
+    // Subcommands
+    "add",
+    "authenticate",
+    "init",
+    "install",
+    "list",
+    "self-install",
+    "self-update",
+    "system-info",
+    "trust",
+    "update",
+    // Windows device names
+    "con",
+    "prn",
+    "aux",
+    "nul",
+    "com1",
+    "com2",
+    "com3",
+    "com4",
+    "com5",
+    "com6",
+    "com7",
+    "com8",
+    "com9",
+    "lpt1",
+    "lpt2",
+    "lpt3",
+    "lpt4",
+    "lpt5",
+    "lpt6",
+    "lpt7",
+    "lpt8",
+    "lpt9",
+];
+
 /**
     A tool alias, which is a simple string identifier for a tool.
 
@@ -52,14 +99,20 @@ impl FromStr for ToolAlias {
         if s.is_empty() {
             return Err(ToolAliasParseError::Empty);
         }
-        if is_invalid_identifier(s) {
+        if s.contains(['/', '\\']) {
+            return Err(ToolAliasParseError::ContainsPathSeparator(s.to_string()));
+        }
+        if is_invalid_identifier(s) || s == "." || s == ".." {
             return Err(ToolAliasParseError::Invalid(s.to_string()));
         }
         if s.chars().any(char::is_whitespace) {
             return Err(ToolAliasParseError::ContainsWhitespace(s.to_string()));
         }
-        if s.eq_ignore_ascii_case("rokit") {
-            return Err(ToolAliasParseError::Invalid(s.to_string()));
+        if RESERVED_NAMES
+            .iter()
+            .any(|reserved| reserved.eq_ignore_ascii_case(s))
+        {
+            return Err(ToolAliasParseError::Reserved(s.to_string()));
         }
         Ok(Self {
             name: CaseInsensitiveString::new(s),
@@ -73,17 +126,27 @@ impl fmt::Display for ToolAlias {
     }
 }
 
-impl From<&ToolId> for ToolAlias {
-    fn from(id: &ToolId) -> Self {
-        Self {
-            name: CaseInsensitiveString::new(id.name()),
-        }
+impl TryFrom<&str> for ToolAlias {
+    type Error = ToolAliasParseError;
+    fn try_from(s: &str) -> Result<Self, Self::Error> {
+        s.parse()
     }
 }
 
-impl From<ToolId> for ToolAlias {
-    fn from(id: ToolId) -> Self {
-        (&id).into()
+// NOTE: Tool names are more permissive than aliases, and may for example
+// be reserved names, so aliases derived from them must also be validated
+
+impl TryFrom<&ToolId> for ToolAlias {
+    type Error = ToolAliasParseError;
+    fn try_from(id: &ToolId) -> Result<Self, Self::Error> {
+        id.name().parse()
+    }
+}
+
+impl TryFrom<ToolId> for ToolAlias {
+    type Error = ToolAliasParseError;
+    fn try_from(id: ToolId) -> Result<Self, Self::Error> {
+        (&id).try_into()
     }
 }
 
@@ -138,6 +201,49 @@ mod tests {
         assert!("to\tol".parse::<ToolAlias>().is_err());
     }
 
+    #[test]
+    fn parse_invalid_path_separator() {
+        for alias in ["a/b", "a\\b", "/tool", "tool/", "..\\tool"] {
+            assert!(matches!(
+                alias.parse::<ToolAlias>(),
+                Err(ToolAliasParseError::ContainsPathSeparator(_))
+            ));
+        }
+        assert!(matches!(
+            "..".parse::<ToolAlias>(),
+            Err(ToolAliasParseError::Invalid(_))
+        ));
+    }
+
+    #[test]
+    fn parse_invalid_reserved() {
+        for alias in ["rokit", "Rokit", "install", "self-update", "NUL", "com1"] {
+            assert!(matches!(
+                alias.parse::<ToolAlias>(),
+                Err(ToolAliasParseError::Reserved(_))
+            ));
+        }
+        // Names that only contain a reserved name are fine
+        assert!("rokit-helper".parse::<ToolAlias>().is_ok());
+        assert!("installer".parse::<ToolAlias>().is_ok());
+        assert!(ToolAlias::try_from("console").is_ok());
+    }
+
+    #[test]
+    fn derived_from_id() {
+        let id = "author/Tool".parse::<ToolId>().unwrap();
+        assert_eq!(ToolAlias::try_from(&id).unwrap(), new_alias("Tool"));
+        assert_eq!(id.into_alias().unwrap().name(), "Tool");
+        // Derived aliases go through the same validation
+        let id = "rojo-rbx/rokit".parse::<ToolId>().unwrap();
+        assert!(matches!(
+            id.into_alias(),
+            Err(ToolAliasParseError::Reserved(_))
+        ));
+        let id = "author/.tool".parse::<ToolId>().unwrap();
+        assert!(ToolAlias::try_from(id).is_ok());
+    }
+
     #[test]
     fn case_sensitivity() {
         // ToolAliases should be case-insensitive
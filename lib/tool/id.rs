@@ -4,9 +4,15 @@ use semver::Version;
 use serde_with::{DeserializeFromStr, SerializeDisplay};
 use thiserror::Error;
 
-use crate::{sources::ArtifactProvider, util::str::CaseInsensitiveString};
+use crate::{
+    sources::ArtifactProvider,
+    util::str::{glob_matches, CaseInsensitiveString},
+};
 
-use super::{util::is_invalid_identifier, ToolAlias, ToolSpec};
+use super::{
+    util::{is_invalid_author, is_invalid_name},
+    ToolAlias, ToolAliasParseError, ToolSpec,
+};
 
 /**
     Error type representing the possible errors that can occur when parsing a `ToolId`.
@@ -44,6 +50,38 @@ pub struct ToolId {
 }
 
 impl ToolId {
+    /**
+        Creates a new tool id from its individual parts, validating
+        the author and name the same way as when parsing a tool id.
+
+        Surrounding whitespace is trimmed, and casing is preserved.
+
+        # Errors
+
+        - If the author or name is empty or invalid.
+    */
+    pub fn new(
+        provider: ArtifactProvider,
+        author: &str,
+        name: &str,
+    ) -> Result<Self, ToolIdParseError> {
+        let author = author.trim();
+        let name = name.trim();
+
+        if is_invalid_author(author) {
+            return Err(ToolIdParseError::InvalidAuthor(author.to_string()));
+        }
+        if is_invalid_name(name) {
+            return Err(ToolIdParseError::InvalidName(name.to_string()));
+        }
+
+        Ok(Self {
+            provider,
+            author: CaseInsensitiveString::new(author),
+            name: CaseInsensitiveString::new(name),
+        })
+    }
+
     #[must_use]
     pub fn provider(&self) -> ArtifactProvider {
         self.provider
@@ -59,14 +97,59 @@ impl ToolId {
         self.name.original_str()
     }
 
+    /**
+        Gets the canonical form of this tool id, which always includes the
+        provider and uses lowercased author and name - `github:author/name`.
+
+        Unlike the [`Display`](fmt::Display) implementation, this is stable across
+        differently cased inputs, and is suitable for use as a cache key.
+    */
+    #[must_use]
+    pub fn canonical(&self) -> String {
+        format!(
+            "{}:{}/{}",
+            self.provider,
+            self.author.uncased_str(),
+            self.name.uncased_str()
+        )
+    }
+
+    /**
+        Checks if this tool id matches a glob-style pattern, where
+        `*` matches any sequence of characters, such as `rojo*`,
+        `roblox-ts/*`, or `*/*` to match all tools.
+
+        Patterns containing a `/` are matched against the full `author/name`
+        form of the id, and other patterns are matched only against the name.
+
+        Matching is case-insensitive, and ignores the provider of the tool.
+    */
+    #[must_use]
+    pub fn matches(&self, pattern: &str) -> bool {
+        let pattern = pattern.trim().to_lowercase();
+        match pattern.split_once('/') {
+            Some((author, name)) => {
+                glob_matches(author.trim(), self.author.uncased_str())
+                    && glob_matches(name.trim(), self.name.uncased_str())
+            }
+            None => glob_matches(&pattern, self.name.uncased_str()),
+        }
+    }
+
     #[must_use]
     pub fn into_spec(self, version: Version) -> ToolSpec {
         ToolSpec::from((self, version))
     }
 
-    #[must_use]
-    pub fn into_alias(self) -> ToolAlias {
-        ToolAlias::from(self)
+    /**
+        Converts this tool id into an alias, using the name of the tool.
+
+        # Errors
+
+        - If the name of the tool is not a valid alias, such as a reserved name.
+    */
+    pub fn into_alias(self) -> Result<ToolAlias, ToolAliasParseError> {
+        ToolAlias::try_from(self)
     }
 }
 
@@ -75,6 +158,7 @@ impl Ord for ToolId {
         self.author
             .cmp(&other.author)
             .then_with(|| self.name.cmp(&other.name))
+            .then_with(|| self.provider.cmp(&other.provider))
     }
 }
 
@@ -91,39 +175,83 @@ impl FromStr for ToolId {
             return Err(ToolIdParseError::Empty);
         }
 
-        let (provider, after_provider) = match s.split_once(':') {
-            None => (ArtifactProvider::default(), s),
-            Some((left, right)) => {
-                let provider = ArtifactProvider::from_str(left)
-                    .map_err(|e| ToolIdParseError::InvalidProvider(e.to_string()))?;
-                (provider, right)
-            }
+        let (provider, after_provider) = match split_repository_url(s) {
+            Some(res) => res?,
+            None => match s.split_once(':') {
+                None => (ArtifactProvider::default(), s),
+                Some((left, right)) => {
+                    let provider = ArtifactProvider::from_str(left)
+                        .map_err(ToolIdParseError::InvalidProvider)?;
+                    (provider, right)
+                }
+            },
         };
 
         let Some((before, after)) = after_provider.split_once('/') else {
             return Err(ToolIdParseError::MissingSeparator);
         };
 
-        let before = before.trim();
-        let after = after.trim();
-
-        if is_invalid_identifier(before) {
-            return Err(ToolIdParseError::InvalidAuthor(before.to_string()));
-        }
-        if is_invalid_identifier(after) {
-            return Err(ToolIdParseError::InvalidName(after.to_string()));
-        }
+        Self::new(provider, before, after)
+    }
+}
 
-        Ok(Self {
-            provider,
-            author: CaseInsensitiveString::new(before),
-            name: CaseInsensitiveString::new(after),
-        })
+impl TryFrom<(ArtifactProvider, &str, &str)> for ToolId {
+    type Error = ToolIdParseError;
+    fn try_from(
+        (provider, author, name): (ArtifactProvider, &str, &str),
+    ) -> Result<Self, Self::Error> {
+        Self::new(provider, author, name)
     }
 }
 
+/**
+    Splits a repository URL, such as `https://github.com/author/name`,
+    into its provider and the remaining `author/name` part of the URL.
+
+    Leading `www.` subdomains, trailing slashes, and `.git` suffixes are stripped.
+
+    Returns `None` if the given string is not a URL.
+*/
+fn split_repository_url(s: &str) -> Option<Result<(ArtifactProvider, &str), ToolIdParseError>> {
+    let s = s.trim();
+    let after_scheme = ["https://", "http://"].into_iter().find_map(|scheme| {
+        let prefix = s.get(..scheme.len())?;
+        if prefix.eq_ignore_ascii_case(scheme) {
+            Some(&s[scheme.len()..])
+        } else {
+            None
+        }
+    })?;
+
+    let (host, path) = after_scheme.split_once('/').unwrap_or((after_scheme, ""));
+    let host = match host.get(..4) {
+        Some(www) if www.eq_ignore_ascii_case("www.") => &host[4..],
+        _ => host,
+    };
+
+    let provider = if host.eq_ignore_ascii_case("github.com") {
+        ArtifactProvider::GitHub
+    } else if host.eq_ignore_ascii_case("gitlab.com") {
+        ArtifactProvider::GitLab
+    } else if host.eq_ignore_ascii_case("bitbucket.org") {
+        ArtifactProvider::Bitbucket
+    } else {
+        return Some(Err(ToolIdParseError::InvalidProvider(host.to_string())));
+    };
+
+    let path = path.trim_end_matches('/');
+    let path = path.strip_suffix(".git").unwrap_or(path);
+
+    Some(Ok((provider, path)))
+}
+
 impl fmt::Display for ToolId {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // NOTE: The default provider is omitted to keep
+        // manifests and other serialized ids short & stable
+        if self.provider != ArtifactProvider::default() {
+            write!(f, "{}:", self.provider)?;
+        }
         write!(
             f,
             "{}/{}",
@@ -188,11 +316,254 @@ mod tests {
     fn parse_valid_provider() {
         // Known provider strings should parse ok
         assert!("github:a/b".parse::<ToolId>().is_ok());
+        assert!("gitlab:a/b".parse::<ToolId>().is_ok());
         // The parsed ToolId should match the input
         assert_eq!(
             "github:a/b".parse::<ToolId>().unwrap(),
             new_id_with_provider(ArtifactProvider::GitHub, "a", "b")
         );
+        assert_eq!(
+            "gitlab:a/b".parse::<ToolId>().unwrap(),
+            new_id_with_provider(ArtifactProvider::GitLab, "a", "b")
+        );
+    }
+
+    #[test]
+    fn parse_valid_provider_case_insensitive() {
+        // Provider prefixes should not be case sensitive
+        assert_eq!(
+            "GITHUB:a/b".parse::<ToolId>().unwrap(),
+            new_id_with_provider(ArtifactProvider::GitHub, "a", "b")
+        );
+        assert_eq!(
+            "GitLab:a/b".parse::<ToolId>().unwrap(),
+            new_id_with_provider(ArtifactProvider::GitLab, "a", "b")
+        );
+        // ... and should serialize using the canonical form
+        let id = "GitLab:a/b".parse::<ToolId>().unwrap();
+        let json = serde_json::to_string(&id).unwrap();
+        assert_eq!(json, "\"gitlab:a/b\"");
+        assert_eq!(serde_json::from_str::<ToolId>(&json).unwrap(), id);
+        let id = "GITHUB:a/b".parse::<ToolId>().unwrap();
+        let json = serde_json::to_string(&id).unwrap();
+        assert_eq!(json, "\"a/b\"");
+        assert_eq!(serde_json::from_str::<ToolId>(&json).unwrap(), id);
+    }
+
+    #[test]
+    fn parse_valid_provider_alias() {
+        // Short provider aliases should parse ok
+        assert_eq!(
+            "gh:a/b".parse::<ToolId>().unwrap(),
+            new_id_with_provider(ArtifactProvider::GitHub, "a", "b")
+        );
+        assert_eq!(
+            "gl:a/b".parse::<ToolId>().unwrap(),
+            new_id_with_provider(ArtifactProvider::GitLab, "a", "b")
+        );
+        // ... but should always display as the canonical name
+        assert_eq!(
+            "gl:a/b".parse::<ToolId>().unwrap().to_string(),
+            "gitlab:a/b"
+        );
+        assert_eq!("gh:a/b".parse::<ToolId>().unwrap().to_string(), "a/b");
+    }
+
+    #[test]
+    fn parse_valid_url() {
+        let github = new_id_with_provider(ArtifactProvider::GitHub, "author", "name");
+        let gitlab = new_id_with_provider(ArtifactProvider::GitLab, "author", "name");
+        // Full repository URLs should parse ok and have the correct provider
+        for url in [
+            "https://github.com/author/name",
+            "http://github.com/author/name",
+            "https://www.github.com/author/name",
+            "https://github.com/author/name/",
+            "https://github.com/author/name.git",
+            "https://github.com/author/name.git/",
+            "HTTPS://GitHub.com/author/name",
+        ] {
+            assert_eq!(url.parse::<ToolId>().unwrap(), github, "{url}");
+        }
+        for url in [
+            "https://gitlab.com/author/name",
+            "https://www.gitlab.com/author/name.git",
+        ] {
+            let id = url.parse::<ToolId>().unwrap();
+            assert_eq!(id, gitlab, "{url}");
+            assert_eq!(id.provider(), ArtifactProvider::GitLab, "{url}");
+        }
+        // URL-parsed ids should round-trip through their short form
+        let id = "https://github.com/Author/Name.git"
+            .parse::<ToolId>()
+            .unwrap();
+        assert_eq!(id.to_string(), "Author/Name");
+        assert_eq!(id.to_string().parse::<ToolId>().unwrap(), id);
+        let id = "https://gitlab.com/Author/Name".parse::<ToolId>().unwrap();
+        assert_eq!(id.to_string(), "gitlab:Author/Name");
+        assert_eq!(id.to_string().parse::<ToolId>().unwrap(), id);
+        let id = "https://bitbucket.org/Author/Name"
+            .parse::<ToolId>()
+            .unwrap();
+        assert_eq!(id.to_string(), "bitbucket:Author/Name");
+        assert_eq!(id.to_string().parse::<ToolId>().unwrap(), id);
+    }
+
+    #[test]
+    fn parse_invalid_url() {
+        // Unknown hosts should not be allowed
+        assert!(matches!(
+            "https://example.com/author/name".parse::<ToolId>(),
+            Err(ToolIdParseError::InvalidProvider(_))
+        ));
+        // Incomplete paths or extra path segments should not be allowed
+        assert!("https://github.com".parse::<ToolId>().is_err());
+        assert!("https://github.com/".parse::<ToolId>().is_err());
+        assert!("https://github.com/author".parse::<ToolId>().is_err());
+        assert!("https://github.com/author/".parse::<ToolId>().is_err());
+        assert!("https://github.com/author/name/releases"
+            .parse::<ToolId>()
+            .is_err());
+    }
+
+    #[test]
+    fn matches_name_wildcards() {
+        let id = new_id("rojo-rbx", "rojo");
+        assert!(id.matches("rojo"));
+        assert!(id.matches("rojo*"));
+        assert!(id.matches("*"));
+        assert!(id.matches("ro*o"));
+        assert!(!id.matches("rojo-*"));
+        assert!(!id.matches("rojo-rbx"));
+        assert!(new_id("rojo-rbx", "rojo-plugin").matches("rojo*"));
+    }
+
+    #[test]
+    fn matches_author_wildcards() {
+        let id = new_id("roblox-ts", "roblox-ts");
+        assert!(id.matches("roblox-ts/*"));
+        assert!(id.matches("*/*"));
+        assert!(id.matches("*/roblox-ts"));
+        assert!(id.matches("roblox-*/*-ts"));
+        assert!(!id.matches("rojo-rbx/*"));
+        assert!(!id.matches("*/rojo"));
+    }
+
+    #[test]
+    fn matches_full_case_insensitive() {
+        let id = new_id("Rojo-Rbx", "Rojo");
+        assert!(id.matches("rojo-rbx/rojo"));
+        assert!(id.matches("ROJO-RBX/ROJO"));
+        assert!(id.matches("Rojo*"));
+        assert!(!id.matches("rojo-rbx/rojo/extra"));
+        assert!(new_id_with_provider(ArtifactProvider::GitLab, "a", "b").matches("*/*"));
+    }
+
+    #[test]
+    fn canonical_form() {
+        let a = "A/B".parse::<ToolId>().unwrap();
+        let b = "github:a/b".parse::<ToolId>().unwrap();
+        assert_eq!(a.canonical(), "github:a/b");
+        assert_eq!(a.canonical(), b.canonical());
+        // Display should still preserve casing and omit the default provider
+        assert_eq!(a.to_string(), "A/B");
+        let c = "gl:Author/Name".parse::<ToolId>().unwrap();
+        assert_eq!(c.canonical(), "gitlab:author/name");
+        // Canonical forms should parse back into the same id
+        assert_eq!(c.canonical().parse::<ToolId>().unwrap(), c);
+    }
+
+    #[test]
+    fn display_provider() {
+        // The default provider should be omitted, others should be kept
+        assert_eq!(new_id("a", "b").to_string(), "a/b");
+        assert_eq!(
+            new_id_with_provider(ArtifactProvider::GitHub, "a", "b").to_string(),
+            "a/b"
+        );
+        assert_eq!(
+            new_id_with_provider(ArtifactProvider::GitLab, "a", "b").to_string(),
+            "gitlab:a/b"
+        );
+        assert_eq!(
+            new_id_with_provider(ArtifactProvider::Bitbucket, "a", "b").to_string(),
+            "bitbucket:a/b"
+        );
+        // Displayed ids should round-trip through parsing
+        for provider in [ArtifactProvider::GitLab, ArtifactProvider::Bitbucket] {
+            let id = new_id_with_provider(provider, "Author", "Name");
+            assert_eq!(id.to_string().parse::<ToolId>().unwrap(), id);
+            assert_eq!(
+                id.to_string().parse::<ToolId>().unwrap().provider(),
+                provider
+            );
+        }
+        assert_eq!(
+            "BitBucket:a/b".parse::<ToolId>().unwrap(),
+            new_id_with_provider(ArtifactProvider::Bitbucket, "a", "b")
+        );
+    }
+
+    #[test]
+    fn provider_sensitivity_eq() {
+        // Tools with the same author and name on different providers are different tools
+        assert_ne!(
+            new_id_with_provider(ArtifactProvider::GitHub, "a", "b"),
+            new_id_with_provider(ArtifactProvider::GitLab, "a", "b")
+        );
+        assert_ne!(
+            new_id_with_provider(ArtifactProvider::GitHub, "a", "b").cmp(&new_id_with_provider(
+                ArtifactProvider::GitLab,
+                "a",
+                "b"
+            )),
+            std::cmp::Ordering::Equal
+        );
+    }
+
+    #[test]
+    fn parse_valid_special_characters() {
+        // Names may contain dots, underscores, and hyphens anywhere
+        assert_eq!(
+            "author/foo.rs".parse::<ToolId>().unwrap(),
+            new_id("author", "foo.rs")
+        );
+        assert_eq!(
+            "my_org/some_tool".parse::<ToolId>().unwrap(),
+            new_id("my_org", "some_tool")
+        );
+        assert!("my-org/.github".parse::<ToolId>().is_ok());
+        assert!("my.org/_tool-".parse::<ToolId>().is_ok());
+    }
+
+    #[test]
+    fn parse_invalid_special_characters() {
+        // Authors must start and end with alphanumerics
+        assert!(matches!(
+            "-author/name".parse::<ToolId>(),
+            Err(ToolIdParseError::InvalidAuthor(_))
+        ));
+        assert!(matches!(
+            "author_/name".parse::<ToolId>(),
+            Err(ToolIdParseError::InvalidAuthor(_))
+        ));
+        // Other characters and directory names should not be allowed
+        assert!(matches!(
+            "author/na+me".parse::<ToolId>(),
+            Err(ToolIdParseError::InvalidName(_))
+        ));
+        assert!(matches!(
+            "author/na me".parse::<ToolId>(),
+            Err(ToolIdParseError::InvalidName(_))
+        ));
+        assert!(matches!(
+            "author/..".parse::<ToolId>(),
+            Err(ToolIdParseError::InvalidName(_))
+        ));
+        assert!(matches!(
+            "au@thor/name".parse::<ToolId>(),
+            Err(ToolIdParseError::InvalidAuthor(_))
+        ));
     }
 
     #[test]
@@ -223,6 +594,52 @@ mod tests {
         assert!("bitbab:a/b".parse::<ToolId>().is_err());
     }
 
+    #[test]
+    fn new_validates_like_parse() {
+        for (author, name) in [
+            ("author", "name"),
+            ("  Author ", " Name  "),
+            ("my.org", "_tool-"),
+            ("-author", "name"),
+            ("author_", "name"),
+            ("au@thor", "name"),
+            ("author", "na+me"),
+            ("author", "na me"),
+            ("author", ".."),
+            ("author", "b/c"),
+            ("", "name"),
+            ("author", ""),
+        ] {
+            let parsed = format!("gitlab:{author}/{name}").parse::<ToolId>();
+            let created = ToolId::new(ArtifactProvider::GitLab, author, name);
+            match (parsed, created) {
+                (Ok(parsed), Ok(created)) => assert_eq!(parsed, created),
+                (Err(parsed), Err(created)) => assert_eq!(
+                    std::mem::discriminant(&parsed),
+                    std::mem::discriminant(&created),
+                    "{author}/{name}"
+                ),
+                (parsed, created) => {
+                    panic!("mismatch for '{author}/{name}': {parsed:?} vs {created:?}")
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn new_preserves_case() {
+        let id = ToolId::new(ArtifactProvider::Bitbucket, "Author", "Name").unwrap();
+        assert_eq!(id.provider(), ArtifactProvider::Bitbucket);
+        assert_eq!(id.author(), "Author");
+        assert_eq!(id.name(), "Name");
+        assert_eq!(
+            id,
+            new_id_with_provider(ArtifactProvider::Bitbucket, "author", "name")
+        );
+        let from_tuple = ToolId::try_from((ArtifactProvider::Bitbucket, "Author", "Name"));
+        assert_eq!(from_tuple.unwrap().to_string(), id.to_string());
+    }
+
     #[test]
     fn case_preservation() {
         // The author and name should be preserved in their original case
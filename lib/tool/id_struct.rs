@@ -0,0 +1,128 @@
+/*
+    An alternative, explicit struct form for (de)serializing a `ToolId`,
+    with separate `provider`, `author`, and `name` fields.
+
+    The default serialized form of a `ToolId` is the `provider:author/name`
+    string, and this module may be used to opt into the struct form instead,
+    using `#[serde(with = "rokit::tool::id_struct")]` on a `ToolId` field.
+*/
+
+use std::str::FromStr;
+
+use serde::{de::Error as _, Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::sources::ArtifactProvider;
+
+use super::{ToolId, ToolIdParseError};
+
+#[derive(Serialize)]
+struct ToolIdFieldsRef<'a> {
+    provider: &'a str,
+    author: &'a str,
+    name: &'a str,
+}
+
+#[derive(Deserialize)]
+struct ToolIdFields {
+    #[serde(default)]
+    provider: Option<String>,
+    author: String,
+    name: String,
+}
+
+/**
+    Serializes a [`ToolId`] in its struct form.
+
+    # Errors
+
+    - If the serializer fails.
+*/
+pub fn serialize<S: Serializer>(id: &ToolId, serializer: S) -> Result<S::Ok, S::Error> {
+    ToolIdFieldsRef {
+        provider: id.provider.as_str(),
+        author: id.author.original_str(),
+        name: id.name.original_str(),
+    }
+    .serialize(serializer)
+}
+
+/**
+    Deserializes a [`ToolId`] from its struct form.
+
+    The provider field is optional, and defaults to the default provider.
+
+    # Errors
+
+    - If the deserializer fails.
+    - If any of the fields are invalid.
+*/
+pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<ToolId, D::Error> {
+    let fields = ToolIdFields::deserialize(deserializer)?;
+    from_fields(&fields).map_err(D::Error::custom)
+}
+
+fn from_fields(fields: &ToolIdFields) -> Result<ToolId, ToolIdParseError> {
+    let provider = match fields.provider.as_deref() {
+        None => ArtifactProvider::default(),
+        Some(provider) => {
+            ArtifactProvider::from_str(provider).map_err(ToolIdParseError::InvalidProvider)?
+        }
+    };
+
+    ToolId::new(provider, &fields.author, &fields.name)
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[derive(Debug, Serialize, Deserialize)]
+    struct Entry {
+        #[serde(with = "super")]
+        id: ToolId,
+    }
+
+    #[test]
+    fn round_trip_struct_form() {
+        let id = "gitlab:Author/Name".parse::<ToolId>().unwrap();
+        let value = serde_json::to_value(Entry { id: id.clone() }).unwrap();
+        assert_eq!(
+            value,
+            json!({ "id": { "provider": "gitlab", "author": "Author", "name": "Name" } })
+        );
+        let entry = serde_json::from_value::<Entry>(value).unwrap();
+        assert_eq!(entry.id, id);
+        assert_eq!(entry.id.author(), "Author");
+    }
+
+    #[test]
+    fn string_and_struct_forms_match() {
+        let from_string = serde_json::from_value::<ToolId>(json!("Author/Name")).unwrap();
+        let from_struct = serde_json::from_value::<Entry>(json!({
+            "id": { "author": "author", "name": "NAME" }
+        }))
+        .unwrap();
+        // Both forms should compare equal, regardless of casing
+        assert_eq!(from_struct.id, from_string);
+        assert_eq!(from_struct.id.provider(), ArtifactProvider::GitHub);
+        // ... while the default string form is left intact
+        assert_eq!(
+            serde_json::to_value(&from_string).unwrap(),
+            json!("Author/Name")
+        );
+    }
+
+    #[test]
+    fn invalid_struct_form() {
+        for value in [
+            json!({ "id": { "provider": "unknown", "author": "a", "name": "b" } }),
+            json!({ "id": { "author": "", "name": "b" } }),
+            json!({ "id": { "author": "a", "name": "b/c" } }),
+            json!({ "id": { "author": "a" } }),
+        ] {
+            assert!(serde_json::from_value::<Entry>(value).is_err());
+        }
+    }
+}
@@ -2,7 +2,11 @@ mod alias;
 mod id;
 mod spec;
 mod util;
+mod version;
+
+pub mod id_struct;
 
 pub use self::alias::{ToolAlias, ToolAliasParseError};
 pub use self::id::{ToolId, ToolIdParseError};
 pub use self::spec::{ToolSpec, ToolSpecParseError};
+pub use self::version::{ToolVersion, ToolVersionSpec};
@@ -184,6 +184,45 @@ mod tests {
         assert!("/@".parse::<ToolSpec>().is_err());
     }
 
+    #[test]
+    fn parse_invalid_error_kinds() {
+        // Missing version separators should be a distinct error
+        assert!(matches!(
+            "author/name".parse::<ToolSpec>(),
+            Err(ToolSpecParseError::MissingVersionSeparator)
+        ));
+        // Invalid ids and invalid versions should be distinguishable
+        assert!(matches!(
+            "author@1.2.3".parse::<ToolSpec>(),
+            Err(ToolSpecParseError::IdParseError(
+                ToolIdParseError::MissingSeparator
+            ))
+        ));
+        assert!(matches!(
+            "unknown:author/name@1.2.3".parse::<ToolSpec>(),
+            Err(ToolSpecParseError::IdParseError(
+                ToolIdParseError::InvalidProvider(_)
+            ))
+        ));
+        assert!(matches!(
+            "author/name@one.two.three".parse::<ToolSpec>(),
+            Err(ToolSpecParseError::VersionParseError(_))
+        ));
+        assert!(matches!(
+            "author/name@^1.2".parse::<ToolSpec>(),
+            Err(ToolSpecParseError::VersionParseErrorSuspectedVersionReq(_))
+        ));
+    }
+
+    #[test]
+    fn parse_valid_provider() {
+        // Provider prefixes should be kept when parsing ToolSpecs
+        let spec = "gitlab:author/name@1.2.3".parse::<ToolSpec>().unwrap();
+        assert_eq!(spec.provider(), ArtifactProvider::GitLab);
+        assert_eq!(spec.version(), &Version::new(1, 2, 3));
+        assert_eq!(spec.to_string(), "gitlab:author/name@1.2.3");
+    }
+
     #[test]
     fn parse_invalid_extra_separator() {
         // Superfluous separators should not be allowed
@@ -7,3 +7,31 @@ pub fn is_invalid_identifier(s: &str) -> bool {
             || c == '@' // Must not contain the version separator character
         )
 }
+
+fn is_valid_name_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.')
+}
+
+/**
+    Checks if the given string is an invalid tool author, following the rules
+    that GitHub and GitLab have for user, organization, and group names.
+
+    Authors may contain alphanumerics, hyphens, underscores, and periods,
+    but must both start and end with an alphanumeric character.
+*/
+pub fn is_invalid_author(s: &str) -> bool {
+    !s.chars().all(is_valid_name_char)
+        || !s.starts_with(|c: char| c.is_ascii_alphanumeric())
+        || !s.ends_with(|c: char| c.is_ascii_alphanumeric())
+}
+
+/**
+    Checks if the given string is an invalid tool name, following the
+    rules that GitHub and GitLab have for repository and project names.
+
+    Names may contain alphanumerics, hyphens, underscores, and periods,
+    but must not be empty, or consist of only the `.` or `..` directories.
+*/
+pub fn is_invalid_name(s: &str) -> bool {
+    s.is_empty() || s == "." || s == ".." || !s.chars().all(is_valid_name_char)
+}
@@ -0,0 +1,360 @@
+use std::{fmt, str::FromStr};
+
+use semver::{Version, VersionReq};
+use serde_with::{DeserializeFromStr, SerializeDisplay};
+
+use super::{util::is_invalid_identifier, ToolId, ToolSpec, ToolSpecParseError};
+
+/**
+    A version of a tool, which may either be an exact version,
+    or a version requirement that can match several versions.
+*/
+#[derive(Debug, Clone, PartialEq, Eq, Hash, DeserializeFromStr, SerializeDisplay)]
+pub enum ToolVersion {
+    Exact(Version),
+    Requirement(VersionReq),
+}
+
+impl ToolVersion {
+    /**
+        Checks if the given version satisfies this tool version.
+
+        Prerelease versions only satisfy requirements that explicitly
+        reference a prerelease of the same `major.minor.patch` version.
+    */
+    #[must_use]
+    pub fn matches(&self, version: &Version) -> bool {
+        match self {
+            Self::Exact(exact) => exact == version,
+            Self::Requirement(req) => req.matches(version),
+        }
+    }
+
+    /**
+        Checks if the given version satisfies this tool version, optionally
+        also letting any prerelease satisfy a version requirement, as long
+        as the release it precedes would satisfy the requirement.
+
+        Exact versions, including exact prereleases, are always honored.
+    */
+    #[must_use]
+    pub fn matches_with(&self, version: &Version, include_prereleases: bool) -> bool {
+        match self {
+            Self::Requirement(req) if include_prereleases && !version.pre.is_empty() => {
+                let release = Version::new(version.major, version.minor, version.patch);
+                req.matches(version) || req.matches(&release)
+            }
+            _ => self.matches(version),
+        }
+    }
+
+    /**
+        Resolves the best match among the given available versions,
+        which is the highest version that satisfies this tool version.
+
+        Returns `None` if no available version is a match.
+    */
+    pub fn resolve<'a>(
+        &self,
+        available: impl IntoIterator<Item = &'a Version>,
+    ) -> Option<&'a Version> {
+        self.resolve_with(available, false)
+    }
+
+    /**
+        Resolves the best match among the given available versions, and
+        optionally includes prereleases, see [`ToolVersion::matches_with`].
+
+        Returns `None` if no available version is a match.
+    */
+    pub fn resolve_with<'a>(
+        &self,
+        available: impl IntoIterator<Item = &'a Version>,
+        include_prereleases: bool,
+    ) -> Option<&'a Version> {
+        available
+            .into_iter()
+            .filter(|version| self.matches_with(version, include_prereleases))
+            .max()
+    }
+}
+
+impl FromStr for ToolVersion {
+    type Err = ToolSpecParseError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        if s.is_empty() {
+            return Err(ToolSpecParseError::Empty);
+        }
+
+        // NOTE: Exact versions must be parsed first, since a
+        // bare version like "1.2.3" is also a valid requirement
+        match s.parse::<Version>() {
+            Ok(version) => Ok(Self::Exact(version)),
+            Err(e) => match s.parse::<VersionReq>() {
+                Ok(req) => Ok(Self::Requirement(req)),
+                Err(_) => Err(ToolSpecParseError::VersionParseError(e)),
+            },
+        }
+    }
+}
+
+impl fmt::Display for ToolVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Exact(version) => version.fmt(f),
+            Self::Requirement(req) => req.fmt(f),
+        }
+    }
+}
+
+impl From<Version> for ToolVersion {
+    fn from(version: Version) -> Self {
+        Self::Exact(version)
+    }
+}
+
+impl From<VersionReq> for ToolVersion {
+    fn from(req: VersionReq) -> Self {
+        Self::Requirement(req)
+    }
+}
+
+/**
+    A tool version specification, which includes the author, name,
+    and either an exact version or a version requirement of a tool.
+
+    This is a relaxed version of [`ToolSpec`], which can be resolved
+    into a [`ToolSpec`] once the available versions of a tool are known.
+*/
+#[derive(Debug, Clone, PartialEq, Eq, Hash, DeserializeFromStr, SerializeDisplay)]
+pub struct ToolVersionSpec {
+    pub(crate) id: ToolId,
+    pub(crate) version: ToolVersion,
+}
+
+impl ToolVersionSpec {
+    #[must_use]
+    pub fn id(&self) -> &ToolId {
+        &self.id
+    }
+
+    #[must_use]
+    pub fn version(&self) -> &ToolVersion {
+        &self.version
+    }
+
+    /**
+        Resolves this version specification into an exact [`ToolSpec`],
+        using the highest available version that satisfies it.
+
+        Returns `None` if no available version is a match.
+    */
+    pub fn resolve<'a>(
+        &self,
+        available: impl IntoIterator<Item = &'a Version>,
+    ) -> Option<ToolSpec> {
+        self.resolve_with(available, false)
+    }
+
+    /**
+        Resolves this version specification into an exact [`ToolSpec`],
+        and optionally includes prereleases, see [`ToolVersion::matches_with`].
+
+        Returns `None` if no available version is a match.
+    */
+    pub fn resolve_with<'a>(
+        &self,
+        available: impl IntoIterator<Item = &'a Version>,
+        include_prereleases: bool,
+    ) -> Option<ToolSpec> {
+        let version = self.version.resolve_with(available, include_prereleases)?;
+        Some((self.id.clone(), version.clone()).into())
+    }
+}
+
+impl FromStr for ToolVersionSpec {
+    type Err = ToolSpecParseError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.is_empty() {
+            return Err(ToolSpecParseError::Empty);
+        }
+
+        let Some((before, after)) = s.split_once('@') else {
+            return Err(ToolSpecParseError::MissingVersionSeparator);
+        };
+
+        let before = before.trim();
+        let after = after.trim();
+
+        let id = before.parse::<ToolId>()?;
+
+        if is_invalid_identifier(after) {
+            return Err(ToolSpecParseError::InvalidVersion(after.to_string()));
+        }
+
+        let version = after.parse::<ToolVersion>()?;
+
+        Ok(Self { id, version })
+    }
+}
+
+impl fmt::Display for ToolVersionSpec {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}@{}", self.id, self.version)
+    }
+}
+
+impl From<ToolSpec> for ToolVersionSpec {
+    fn from(spec: ToolSpec) -> Self {
+        Self {
+            id: spec.id,
+            version: ToolVersion::Exact(spec.version),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const AVAILABLE: &[&str] = &[
+        "0.9.0",
+        "1.0.0",
+        "1.2.0",
+        "1.2.5",
+        "1.3.0",
+        "2.0.0-rc.1",
+        "2.0.0",
+        "2.1.0",
+        "3.0.0-beta.1",
+    ];
+
+    fn available() -> Vec<Version> {
+        AVAILABLE.iter().map(|v| v.parse().unwrap()).collect()
+    }
+
+    fn resolve(version: &str) -> Option<String> {
+        let available = available();
+        let version = version.parse::<ToolVersion>().unwrap();
+        version.resolve(&available).map(ToString::to_string)
+    }
+
+    #[test]
+    fn parse_exact_and_requirement() {
+        assert!(matches!(
+            "1.2.3".parse::<ToolVersion>(),
+            Ok(ToolVersion::Exact(_))
+        ));
+        assert!(matches!(
+            "^1.2".parse::<ToolVersion>(),
+            Ok(ToolVersion::Requirement(_))
+        ));
+        assert!(matches!(
+            ">=2.1, <3".parse::<ToolVersion>(),
+            Ok(ToolVersion::Requirement(_))
+        ));
+        assert!("not a version".parse::<ToolVersion>().is_err());
+        assert!("".parse::<ToolVersion>().is_err());
+    }
+
+    #[test]
+    fn resolve_exact() {
+        assert_eq!(resolve("1.2.0").as_deref(), Some("1.2.0"));
+        assert_eq!(resolve("2.0.0-rc.1").as_deref(), Some("2.0.0-rc.1"));
+        assert_eq!(resolve("1.2.1"), None);
+    }
+
+    #[test]
+    fn resolve_caret() {
+        assert_eq!(resolve("^1").as_deref(), Some("1.3.0"));
+        assert_eq!(resolve("^1.2").as_deref(), Some("1.3.0"));
+        assert_eq!(resolve("^0.9").as_deref(), Some("0.9.0"));
+        assert_eq!(resolve("^2").as_deref(), Some("2.1.0"));
+        assert_eq!(resolve("^4"), None);
+    }
+
+    #[test]
+    fn resolve_tilde() {
+        assert_eq!(resolve("~1.2").as_deref(), Some("1.2.5"));
+        assert_eq!(resolve("~1.2.1").as_deref(), Some("1.2.5"));
+        assert_eq!(resolve("~1").as_deref(), Some("1.3.0"));
+        assert_eq!(resolve("~1.4"), None);
+    }
+
+    #[test]
+    fn resolve_wildcard() {
+        assert_eq!(resolve("*").as_deref(), Some("2.1.0"));
+        assert_eq!(resolve("1.*").as_deref(), Some("1.3.0"));
+        assert_eq!(resolve("1.2.*").as_deref(), Some("1.2.5"));
+    }
+
+    #[test]
+    fn resolve_range() {
+        assert_eq!(resolve(">=1.2, <2").as_deref(), Some("1.3.0"));
+        assert_eq!(resolve(">=2.1, <3").as_deref(), Some("2.1.0"));
+    }
+
+    #[test]
+    fn resolve_prerelease() {
+        // Prereleases should not match unless explicitly referenced
+        assert_eq!(resolve(">=3").as_deref(), None);
+        assert_eq!(resolve(">2.1").as_deref(), None);
+        assert_eq!(resolve(">=3.0.0-alpha").as_deref(), Some("3.0.0-beta.1"));
+        assert_eq!(resolve("^2.0.0-rc.1").as_deref(), Some("2.1.0"));
+    }
+
+    fn resolve_with(version: &str, include_prereleases: bool) -> Option<String> {
+        let available = available();
+        let version = version.parse::<ToolVersion>().unwrap();
+        version
+            .resolve_with(&available, include_prereleases)
+            .map(ToString::to_string)
+    }
+
+    #[test]
+    fn resolve_including_prereleases() {
+        // Prereleases should be excluded by default ...
+        assert_eq!(resolve_with("*", false).as_deref(), Some("2.1.0"));
+        assert_eq!(resolve_with("^3", false), None);
+        // ... and included when opted in
+        assert_eq!(resolve_with("*", true).as_deref(), Some("3.0.0-beta.1"));
+        assert_eq!(resolve_with("^3", true).as_deref(), Some("3.0.0-beta.1"));
+        assert_eq!(resolve_with(">=2, <3", true).as_deref(), Some("2.1.0"));
+        // Stable releases are still preferred over their own prereleases
+        assert_eq!(resolve_with("~2.0", true).as_deref(), Some("2.0.0"));
+    }
+
+    #[test]
+    fn resolve_pinned_prereleases() {
+        // Explicitly pinned prereleases are always honored
+        for include_prereleases in [false, true] {
+            assert_eq!(
+                resolve_with("2.0.0-rc.1", include_prereleases).as_deref(),
+                Some("2.0.0-rc.1")
+            );
+            assert_eq!(
+                resolve_with(">=3.0.0-alpha", include_prereleases).as_deref(),
+                Some("3.0.0-beta.1")
+            );
+        }
+    }
+
+    #[test]
+    fn spec_parse_and_resolve() {
+        let available = available();
+        let spec = "author/name@^1.2".parse::<ToolVersionSpec>().unwrap();
+        assert_eq!(spec.to_string(), "author/name@^1.2");
+        let resolved = spec.resolve(&available).unwrap();
+        assert_eq!(resolved, "author/name@1.3.0".parse::<ToolSpec>().unwrap());
+        // Exact specs should resolve to themselves
+        let exact = "author/name@1.2.0".parse::<ToolSpec>().unwrap();
+        let spec = ToolVersionSpec::from(exact.clone());
+        assert_eq!(spec.resolve(&available), Some(exact));
+        // Missing separators should still be an error
+        assert!(matches!(
+            "author/name".parse::<ToolVersionSpec>(),
+            Err(ToolSpecParseError::MissingVersionSeparator)
+        ));
+    }
+}
@@ -59,6 +59,22 @@ pub async fn path_exists(path: impl AsRef<Path>) -> bool {
 pub async fn write_executable_file(
     path: impl AsRef<Path>,
     contents: impl AsRef<[u8]>,
+) -> RokitResult<()> {
+    write_executable_file_with_mode(path, contents, None).await
+}
+
+/**
+    Writes the given contents to the file at the given path, and adds
+    executable permissions to it, based on the given unix file mode.
+
+    The permission bits of the mode are used as-is, with the execute
+    bits always added, and files without a mode are made `rwxr-xr-x`.
+    File modes are ignored on platforms that do not support them.
+*/
+pub async fn write_executable_file_with_mode(
+    path: impl AsRef<Path>,
+    contents: impl AsRef<[u8]>,
+    mode: Option<u32>,
 ) -> RokitResult<()> {
     let path = path.as_ref();
 
@@ -78,19 +94,29 @@ pub async fn write_executable_file(
         return Err(e.into());
     }
 
-    add_executable_permissions(path).await?;
+    add_executable_permissions(path, mode).await?;
 
     Ok(())
 }
 
+/*
+    Gets the permissions to use for an executable with the given unix file
+    mode - special bits such as setuid, and the file type, are never kept.
+*/
+#[cfg(unix)]
+fn executable_permission_bits(mode: Option<u32>) -> u32 {
+    mode.map_or(0o755, |mode| (mode & 0o777) | 0o111)
+}
+
 #[cfg(unix)]
-async fn add_executable_permissions(path: impl AsRef<Path>) -> RokitResult<()> {
+async fn add_executable_permissions(path: impl AsRef<Path>, mode: Option<u32>) -> RokitResult<()> {
     use std::fs::Permissions;
     use std::os::unix::fs::PermissionsExt;
     use tokio::fs::set_permissions;
 
     let path = path.as_ref();
-    if let Err(e) = set_permissions(path, Permissions::from_mode(0o755)).await {
+    let perms = Permissions::from_mode(executable_permission_bits(mode));
+    if let Err(e) = set_permissions(path, perms).await {
         error!("Failed to set executable permissions on {path:?}:\n{e}");
         return Err(e.into());
     }
@@ -99,6 +125,49 @@ async fn add_executable_permissions(path: impl AsRef<Path>) -> RokitResult<()> {
 }
 
 #[cfg(not(unix))]
-async fn add_executable_permissions(_path: impl AsRef<Path>) -> RokitResult<()> {
+async fn add_executable_permissions(
+    _path: impl AsRef<Path>,
+    _mode: Option<u32>,
+) -> RokitResult<()> {
     Ok(())
 }
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn executable_files_have_exec_perms() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("tool");
+        write_executable_file(&path, b"binary contents")
+            .await
+            .unwrap();
+
+        let mode = metadata(&path).await.unwrap().permissions().mode();
+        assert_eq!(mode & 0o111, 0o111, "mode was {mode:o}");
+        assert_eq!(tokio::fs::read(&path).await.unwrap(), b"binary contents");
+    }
+
+    #[tokio::test]
+    async fn executable_files_keep_modes() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempfile::tempdir().unwrap();
+        for (mode, expected) in [
+            (Some(0o100_644), 0o755),
+            (Some(0o100_700), 0o711),
+            (Some(0o104_750), 0o751),
+            (None, 0o755),
+        ] {
+            let path = dir.path().join("tool");
+            write_executable_file_with_mode(&path, b"binary contents", mode)
+                .await
+                .unwrap();
+            let actual = metadata(&path).await.unwrap().permissions().mode() & 0o7777;
+            assert_eq!(actual, expected, "mode was {actual:o}");
+        }
+    }
+}
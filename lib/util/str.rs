@@ -9,6 +9,38 @@ pub(crate) const fn char_is_word_separator(c: char) -> bool {
     c.is_ascii_whitespace() || matches!(c, '-' | '_')
 }
 
+/**
+    Checks if the given string matches a glob-style pattern,
+    where `*` matches any sequence of characters, including none.
+
+    For internal use only.
+*/
+pub(crate) fn glob_matches(pattern: &str, s: &str) -> bool {
+    let pattern = pattern.chars().collect::<Vec<_>>();
+    let s = s.chars().collect::<Vec<_>>();
+
+    // Iterative matching with backtracking to the most recent wildcard
+    let (mut p, mut i) = (0, 0);
+    let mut last_star: Option<(usize, usize)> = None;
+    while i < s.len() {
+        if p < pattern.len() && pattern[p] == '*' {
+            last_star = Some((p, i));
+            p += 1;
+        } else if p < pattern.len() && pattern[p] == s[i] {
+            p += 1;
+            i += 1;
+        } else if let Some((star_p, star_i)) = last_star {
+            p = star_p + 1;
+            i = star_i + 1;
+            last_star = Some((star_p, star_i + 1));
+        } else {
+            return false;
+        }
+    }
+
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
 /**
     A case-insensitive string wrapper.
 
@@ -85,3 +117,29 @@ impl fmt::Display for CaseInsensitiveString {
         self.original_str().fmt(f)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn glob_literal() {
+        assert!(glob_matches("abc", "abc"));
+        assert!(!glob_matches("abc", "abcd"));
+        assert!(!glob_matches("abcd", "abc"));
+        assert!(glob_matches("", ""));
+    }
+
+    #[test]
+    fn glob_wildcards() {
+        assert!(glob_matches("*", ""));
+        assert!(glob_matches("*", "anything"));
+        assert!(glob_matches("ab*", "abc"));
+        assert!(glob_matches("*bc", "abc"));
+        assert!(glob_matches("a*c", "abbbc"));
+        assert!(glob_matches("a*b*c", "aXbYbZc"));
+        assert!(glob_matches("**", "abc"));
+        assert!(!glob_matches("a*c", "abcd"));
+        assert!(!glob_matches("*x*", "abc"));
+    }
+}
@@ -36,7 +36,7 @@ impl AddSubcommand {
         let id: ToolId = self.tool.clone().into();
         let alias: ToolAlias = match self.alias.as_ref() {
             Some(alias) => alias.clone(),
-            None => self.tool.clone().into(),
+            None => ToolAlias::try_from(self.tool.clone())?,
         };
 
         let tool_cache = home.tool_cache();
@@ -48,7 +48,7 @@ impl AddSubcommand {
             if !self.force && !prompt_for_trust(id.clone()).await? {
                 bail!("Tool is not trusted - operation was aborted");
             }
-            let _ = tool_cache.add_trust(id.clone());
+            let _ = tool_cache.add_trust(&id);
         }
 
         // 2. Load manifest and do a preflight check to
@@ -113,7 +113,9 @@ impl AddSubcommand {
                 .extract_contents(contents)
                 .await
                 .with_context(|| format!("Failed to extract contents for {spec}"))?;
-            tool_storage.replace_tool_contents(&spec, extracted).await?;
+            tool_storage
+                .replace_tool_contents(&spec, &extracted)
+                .await?;
             pt.task_completed();
             let _ = tool_cache.add_installed(spec.clone());
         } else {
@@ -4,7 +4,10 @@ use clap::Parser;
 use console::style;
 use rokit::{
     manifests::AuthManifest,
-    sources::{github::GithubProvider, ArtifactProvider},
+    sources::{
+        bitbucket::BitbucketProvider, github::GithubProvider, gitlab::GitlabProvider,
+        ArtifactProvider,
+    },
     storage::Home,
 };
 
@@ -125,6 +128,9 @@ async fn verify_token(
             ArtifactProvider::GitHub => {
                 is_gh_classic_token(token) || is_gh_fine_grained_token(token)
             }
+            ArtifactProvider::GitLab => is_gl_personal_token(token),
+            // NOTE: Bitbucket access tokens have no documented format
+            ArtifactProvider::Bitbucket => true,
         };
 
         if !validated {
@@ -134,6 +140,8 @@ async fn verify_token(
                     format!("{bullet} Starting with 'gh' followed by a lowercase letter and an underscore"),
                     format!("{bullet} Starting with 'github_pat_'"),
                 ],
+                ArtifactProvider::GitLab => vec![format!("{bullet} Starting with 'glpat-'")],
+                ArtifactProvider::Bitbucket => Vec::new(),
             };
 
             let styled_flag = style("--skip-parse").bold().green();
@@ -167,6 +175,16 @@ async fn verify_token(
                 let verify_res = client.verify_authentication().await;
                 verify_res.context("GitHub API returned an error during token verification")?
             }
+            ArtifactProvider::GitLab => {
+                let client = GitlabProvider::new_authenticated(token)?;
+                let verify_res = client.verify_authentication().await;
+                verify_res.context("GitLab API returned an error during token verification")?
+            }
+            ArtifactProvider::Bitbucket => {
+                let client = BitbucketProvider::new_authenticated(token)?;
+                let verify_res = client.verify_authentication().await;
+                verify_res.context("Bitbucket API returned an error during token verification")?
+            }
         };
 
         if !verified {
@@ -202,3 +220,7 @@ fn is_gh_classic_token(token: &str) -> bool {
 fn is_gh_fine_grained_token(token: &str) -> bool {
     token.starts_with("github_pat_")
 }
+
+fn is_gl_personal_token(token: &str) -> bool {
+    token.starts_with("glpat-")
+}
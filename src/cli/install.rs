@@ -4,13 +4,23 @@ use anyhow::{Context, Result};
 use clap::Parser;
 
 use console::style;
-use futures::{stream::FuturesUnordered, TryStreamExt};
-use rokit::{discovery::discover_all_manifests, storage::Home};
+use futures::{
+    stream::{FuturesOrdered, FuturesUnordered},
+    TryStreamExt,
+};
+use rokit::{
+    descriptor::Descriptor,
+    discovery::discover_all_manifests,
+    sources::{ArtifactSource, Release, ResolvedInstall},
+    storage::{ArtifactCache, Home},
+    tool::ToolSpec,
+};
 
 use crate::util::{find_most_compatible_artifact, prompt_for_trust_specs, CliProgressTracker};
 
 /// Adds a new tool using Rokit and installs it.
 #[derive(Debug, Parser)]
+#[allow(clippy::struct_excessive_bools)]
 pub struct InstallSubcommand {
     /// Skip checking if tools have been trusted before.
     /// It is recommended to only use this on CI machines.
@@ -19,17 +29,32 @@ pub struct InstallSubcommand {
     /// Force install all tools, even if they are already installed.
     #[clap(long)]
     pub force: bool,
+    /// Only install tools from previously downloaded artifacts,
+    /// without making any requests to artifact providers.
+    #[clap(long)]
+    pub offline: bool,
+    /// Only print the versions and artifacts that would be installed,
+    /// without downloading, trusting, or installing any tools.
+    #[clap(long)]
+    pub dry_run: bool,
+    /// Also install companion files matching the given pattern, such as
+    /// `completions/*` or `*.so`, next to the binary of each tool.
+    /// May be given several times.
+    #[clap(long = "include", value_name = "PATTERN")]
+    pub include: Vec<String>,
 }
 
 impl InstallSubcommand {
     pub async fn run(self, home: &Home) -> Result<()> {
         let force = self.force;
+        let include = self.include.iter().map(String::as_str).collect::<Vec<_>>();
 
         let source = home.artifact_source().await?;
         let manifests = discover_all_manifests(false, false).await;
 
         let tool_cache = home.tool_cache();
         let tool_storage = home.tool_storage();
+        let artifact_cache = home.artifact_cache().clone().with_offline(self.offline);
 
         // 1. Gather tool specifications from all known manifests
 
@@ -50,6 +75,14 @@ impl InstallSubcommand {
             .map(|(_, spec)| spec)
             .collect::<BTreeSet<_>>();
 
+        if self.dry_run {
+            let pending_specs = tool_specs
+                .into_iter()
+                .filter(|spec| !tool_cache.is_installed(spec) || force)
+                .collect::<BTreeSet<_>>();
+            return print_resolved_installs(&source, &artifact_cache, &pending_specs).await;
+        }
+
         let tool_specs = if self.no_trust_check {
             tool_specs
         } else {
@@ -58,7 +91,7 @@ impl InstallSubcommand {
                 .partition(|spec| tool_cache.is_trusted(spec.id()));
             let newly_trusted_specs = prompt_for_trust_specs(untrusted_specs).await?;
             for spec in &newly_trusted_specs {
-                let _ = tool_cache.add_trust(spec.id().clone());
+                let _ = tool_cache.add_trust(spec.id());
             }
             trusted_specs
                 .iter()
@@ -70,7 +103,7 @@ impl InstallSubcommand {
         // 3. Find artifacts, download and install them
 
         let pt =
-            CliProgressTracker::new_with_message_and_subtasks("Installing", tool_specs.len(), 5);
+            CliProgressTracker::new_with_message_and_subtasks("Installing", tool_specs.len(), 4);
         let installed_specs = tool_specs
             .into_iter()
             .map(|tool_spec| async {
@@ -81,28 +114,36 @@ impl InstallSubcommand {
                     return anyhow::Ok(tool_spec);
                 }
 
-                let release_artifact = source.get_specific_release(&tool_spec).await?;
+                let release_artifact = get_release(&source, &artifact_cache, &tool_spec).await?;
                 pt.subtask_completed();
 
                 let artifact =
                     find_most_compatible_artifact(&release_artifact.artifacts, tool_spec.id())?;
                 pt.subtask_completed();
 
-                let contents = source
-                    .download_artifact_contents(&artifact)
+                let extracted = artifact_cache
+                    .download_and_extract_contents(&source, &artifact)
                     .await
-                    .with_context(|| format!("Failed to download contents for {tool_spec}"))?;
-                pt.subtask_completed();
-
-                let extracted = artifact
-                    .extract_contents(contents)
-                    .await
-                    .with_context(|| format!("Failed to extract contents for {tool_spec}"))?;
+                    .with_context(|| {
+                        format!("Failed to download and extract contents for {tool_spec}")
+                    })?;
                 pt.subtask_completed();
 
                 tool_storage
-                    .replace_tool_contents(&tool_spec, extracted)
+                    .replace_tool_contents(&tool_spec, &extracted)
                     .await?;
+                if !include.is_empty() {
+                    // NOTE: The artifact was cached when extracting the binary above,
+                    // so this only downloads it again if it could not be cached
+                    let contents = artifact_cache
+                        .download_artifact_contents(&source, &artifact)
+                        .await?;
+                    let files = artifact
+                        .extract_included_files(contents, &include)
+                        .await
+                        .with_context(|| format!("Failed to extract files for {tool_spec}"))?;
+                    tool_storage.write_tool_files(&tool_spec, &files).await?;
+                }
                 pt.subtask_completed();
 
                 let _ = tool_cache.add_installed(tool_spec.clone());
@@ -135,3 +176,59 @@ impl InstallSubcommand {
         Ok(())
     }
 }
+
+async fn get_release(
+    source: &ArtifactSource,
+    artifact_cache: &ArtifactCache,
+    tool_spec: &ToolSpec,
+) -> Result<Release> {
+    // NOTE: Offline installs may only use previously downloaded artifacts
+    let release = if artifact_cache.is_offline() {
+        artifact_cache.get_cached_release(tool_spec).await?
+    } else {
+        source.get_specific_release(tool_spec).await?
+    };
+    Ok(release)
+}
+
+async fn print_resolved_installs(
+    source: &ArtifactSource,
+    artifact_cache: &ArtifactCache,
+    tool_specs: &BTreeSet<ToolSpec>,
+) -> Result<()> {
+    let system = Descriptor::current_system();
+    let resolved = tool_specs
+        .iter()
+        .map(|tool_spec| async move {
+            let release = get_release(source, artifact_cache, tool_spec).await?;
+            ResolvedInstall::from_release(&release, system)
+                .with_context(|| format!("No compatible artifact found for {tool_spec}"))
+        })
+        .collect::<FuturesOrdered<_>>()
+        .try_collect::<Vec<_>>()
+        .await?;
+
+    let bullet = style("•").dim();
+    let mut lines = vec![format!(
+        "🔍 Would install {} tool{} for {system}:",
+        style(resolved.len()).bold().magenta(),
+        if resolved.len() == 1 { "" } else { "s" },
+    )];
+    for install in resolved {
+        lines.push(format!("{bullet} {}", style(install.spec()).bold().cyan()));
+        lines.push(format!(
+            "    {} {}",
+            style("asset:").dim(),
+            install.asset_name.as_deref().unwrap_or("N/A"),
+        ));
+        if let Some(url) = &install.asset_url {
+            lines.push(format!("    {} {url}", style("url:").dim()));
+        }
+        if let Some(asset_system) = install.asset_system {
+            lines.push(format!("    {} {asset_system}", style("detected:").dim()));
+        }
+    }
+    println!("{}", lines.join("\n"));
+
+    Ok(())
+}
@@ -80,7 +80,7 @@ impl SelfUpdateSubcommand {
         // Extract the binary contents from the artifact
         pt.task_completed();
         pt.update_message("Extracting");
-        let binary_contents = artifact
+        let binary = artifact
             .extract_contents(artifact_contents)
             .await
             .context("Failed to extract Rokit binary from archive")?;
@@ -90,7 +90,7 @@ impl SelfUpdateSubcommand {
         pt.update_message("Linking");
 
         let storage = home.tool_storage();
-        storage.replace_rokit_contents(binary_contents).await;
+        storage.replace_rokit_contents(binary.contents).await;
         storage
             .recreate_all_links()
             .await
@@ -27,7 +27,7 @@ impl TrustSubcommand {
         let (added_tools, existing_tools) = self
             .tools
             .into_iter()
-            .partition::<Vec<_>, _>(|tool| cache.add_trust(tool.clone()));
+            .partition::<Vec<_>, _>(|tool| cache.add_trust(tool));
 
         if added_tools.len() == 1 && existing_tools.is_empty() {
             // Special case 1 with shorter output - a singular tool was added
@@ -2,7 +2,7 @@ use std::str::FromStr;
 
 use serde_with::DeserializeFromStr;
 
-use rokit::tool::{ToolAlias, ToolId, ToolSpec};
+use rokit::tool::{ToolAlias, ToolAliasParseError, ToolId, ToolSpec};
 
 /**
     A tool alias *or* identifier *or* specification.
@@ -47,13 +47,14 @@ impl From<ToolSpec> for ToolAliasOrIdOrSpec {
     }
 }
 
-impl From<ToolAliasOrIdOrSpec> for ToolAlias {
-    fn from(id_or_spec: ToolAliasOrIdOrSpec) -> Self {
+impl TryFrom<ToolAliasOrIdOrSpec> for ToolAlias {
+    type Error = ToolAliasParseError;
+    fn try_from(id_or_spec: ToolAliasOrIdOrSpec) -> Result<Self, Self::Error> {
         let name = match id_or_spec {
             ToolAliasOrIdOrSpec::Alias(alias) => alias.name().to_string(),
             ToolAliasOrIdOrSpec::Id(id) => id.name().to_string(),
             ToolAliasOrIdOrSpec::Spec(spec) => spec.name().to_string(),
         };
-        Self::from_str(&name).expect("Derived alias is always valid")
+        Self::from_str(&name)
     }
 }
@@ -2,7 +2,7 @@ use std::str::FromStr;
 
 use serde_with::DeserializeFromStr;
 
-use rokit::tool::{ToolAlias, ToolId, ToolSpec};
+use rokit::tool::{ToolAlias, ToolAliasParseError, ToolId, ToolSpec};
 
 use super::constants::get_known_tool;
 
@@ -60,12 +60,13 @@ impl From<ToolIdOrSpec> for ToolId {
     }
 }
 
-impl From<ToolIdOrSpec> for ToolAlias {
-    fn from(id_or_spec: ToolIdOrSpec) -> Self {
+impl TryFrom<ToolIdOrSpec> for ToolAlias {
+    type Error = ToolAliasParseError;
+    fn try_from(id_or_spec: ToolIdOrSpec) -> Result<Self, Self::Error> {
         let name = match id_or_spec {
             ToolIdOrSpec::Id(id) => id.name().to_string(),
             ToolIdOrSpec::Spec(spec) => spec.name().to_string(),
         };
-        Self::from_str(&name).expect("Derived alias is always valid")
+        Self::from_str(&name)
     }
 }